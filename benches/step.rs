@@ -0,0 +1,107 @@
+//! Stepping benchmarks, run with `cargo bench` (and `--features rayon` to
+//! compare stepping on one thread against all of them).
+
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use game_of_life::game::{Board, Boundary, Evolve, SparseBoard};
+use rand::{rngs::StdRng, SeedableRng};
+
+/// A `size` x `size` torus with a glider every `spacing` cells, which keeps
+/// the same few live cells however long it runs.
+fn gliders(size: usize, spacing: usize) -> Board {
+    let mut grid = vec![vec![false; size]; size];
+    for row in (0..size - 2).step_by(spacing) {
+        for col in (0..size - 2).step_by(spacing) {
+            grid[row][col + 1] = true;
+            grid[row + 1][col + 2] = true;
+            grid[row + 2][col..col + 3].fill(true);
+        }
+    }
+    let mut board = Board::new(grid);
+    board.boundary = Boundary::Toroidal;
+    board
+}
+
+/// A `size` x `size` soup with a third of the cells alive.
+fn soup(size: usize) -> Board {
+    Board::random(size, size, 0.3, &mut StdRng::seed_from_u64(0)).unwrap()
+}
+
+/// Steps every cell into a copy of the grid, like boards did before they
+/// reused a scratch buffer or stepped sparse boards by their live cells.
+fn step_every_cell(board: &mut Board) -> i32 {
+    let mut next = board.grid.clone();
+    let mut delta = 0;
+    for (row, cells) in next.iter_mut().enumerate() {
+        for (col, cell) in cells.iter_mut().enumerate() {
+            let (alive, changed) = board.interact(row, col);
+            *cell = alive;
+            delta += changed as i32;
+        }
+    }
+    board.grid = next;
+    delta
+}
+
+// 400 gliders are 0.2% of the cells, so Board::next steps them sparse
+fn sparse(c: &mut Criterion) {
+    let mut board = gliders(1000, 50);
+    board.next();
+
+    let mut group = c.benchmark_group("sparse 1000x1000");
+    group.bench_function("every cell", |b| {
+        b.iter_batched_ref(|| board.clone(), step_every_cell, BatchSize::LargeInput)
+    });
+    group.bench_function("Board::next", |b| {
+        b.iter_batched_ref(|| board.clone(), Board::next, BatchSize::LargeInput)
+    });
+    group.bench_function("SparseBoard::next", |b| {
+        b.iter_batched_ref(
+            || SparseBoard::from(&board),
+            |sparse| sparse.next(),
+            BatchSize::LargeInput,
+        )
+    });
+    group.finish();
+}
+
+// a soup is dense, so Board::next steps it into its scratch buffer
+fn dense(c: &mut Criterion) {
+    let mut board = soup(500);
+    board.next();
+
+    let mut group = c.benchmark_group("dense 500x500");
+    group.bench_function("cloned grid", |b| {
+        b.iter_batched_ref(|| board.clone(), step_every_cell, BatchSize::LargeInput)
+    });
+    group.bench_function("Board::next", |b| {
+        b.iter_batched_ref(|| board.clone(), Board::next, BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+// rows of boards this large step in parallel with the rayon feature
+#[cfg(feature = "rayon")]
+fn parallel(c: &mut Criterion) {
+    let mut board = soup(1000);
+    board.next();
+    let one_thread = rayon::ThreadPoolBuilder::new()
+        .num_threads(1)
+        .build()
+        .unwrap();
+
+    let mut group = c.benchmark_group("dense 1000x1000");
+    group.bench_function("1 thread", |b| {
+        one_thread
+            .install(|| b.iter_batched_ref(|| board.clone(), Board::next, BatchSize::LargeInput))
+    });
+    group.bench_function("all threads", |b| {
+        b.iter_batched_ref(|| board.clone(), Board::next, BatchSize::LargeInput)
+    });
+    group.finish();
+}
+
+#[cfg(feature = "rayon")]
+criterion_group!(benches, sparse, dense, parallel);
+#[cfg(not(feature = "rayon"))]
+criterion_group!(benches, sparse, dense);
+criterion_main!(benches);
@@ -0,0 +1,11 @@
+#![no_main]
+
+use game_of_life::game::{Board, Game};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (Vec<(i32, i32)>, usize, usize)| {
+    let (coords, rows, cols) = input;
+    if let Ok(board) = Board::from_centered_coords(&coords, rows, cols) {
+        Game::from(board).next();
+    }
+});
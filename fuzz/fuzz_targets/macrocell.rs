@@ -0,0 +1,10 @@
+#![no_main]
+
+use game_of_life::game::{Board, Game};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    if let Ok(board) = Board::from_macrocell(input) {
+        Game::from(board).next();
+    }
+});
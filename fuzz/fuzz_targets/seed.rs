@@ -0,0 +1,38 @@
+#![no_main]
+
+use game_of_life::game::{Board, Game, SeedOptions};
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: (
+    char,
+    char,
+    char,
+    bool,
+    bool,
+    bool,
+    bool,
+    Option<u16>,
+    Option<u16>,
+    String
+)| {
+    let (alive, dead, separator, trim, lenient, strict, auto, rows, cols, seed) = input;
+    let mut opts = SeedOptions {
+        rows: rows.map(usize::from),
+        cols: cols.map(usize::from),
+        strict_dimensions: strict,
+        ..SeedOptions::new(
+            Some(alive),
+            Some(dead),
+            Some(separator),
+            Some(trim),
+            Some(lenient),
+        )
+    };
+    if auto {
+        opts.detect_separator(&seed);
+    }
+
+    if let Ok(board) = Board::from_seed(seed, opts) {
+        Game::from(board).next();
+    }
+});
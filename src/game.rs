@@ -1,17 +1,116 @@
-use serde::{Deserialize, Serialize};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use http::StatusCode;
+use rand::{seq::SliceRandom, Rng};
+use serde::{ser::SerializeStruct, Deserialize, Serialize};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::{DefaultHasher, Hash, Hasher};
 use thiserror::Error;
 
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum RuleError {
+    #[error("invalid rule: '{0}', expected B.../S... like B3/S23")]
+    InvalidFormat(String),
+    #[error("invalid neighbor count in rule: '{0}', expected 0 to 8")]
+    InvalidCount(char),
+}
+
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum BoardError {
     #[error("invalid seed separator: {0}")]
     InvalidSeparator(char),
-    #[error("invalid seed character: '{0}', expected '{1}' or '{2}'")]
-    InvalidSeedCharacter(char, char, char),
+    /// `row` and `col` are zero-based, like the board's coordinates.
+    #[error(
+        "invalid seed character: '{found}' at row {row}, col {col}, expected '{alive}' or '{dead}'"
+    )]
+    InvalidSeedCharacter {
+        found: char,
+        alive: char,
+        dead: char,
+        row: usize,
+        col: usize,
+    },
+    #[error("invalid macrocell: {0}")]
+    InvalidMacrocell(String),
+    #[error("seed is empty")]
+    EmptySeed,
+    #[error("invalid rle header: {0}")]
+    InvalidRleHeader(String),
+    #[error("unexpected rle tag: '{0}', expected 'b', 'o', '$' or '!'")]
+    UnexpectedRleTag(char),
+    #[error("invalid life 1.06: {0}")]
+    InvalidLife106(String),
+    #[error("board too large: {rows}x{cols} exceeds {max} cells")]
+    TooLarge {
+        rows: usize,
+        cols: usize,
+        max: usize,
+    },
+    #[error("board too large: {rows}x{cols} exceeds {max_rows}x{max_cols}")]
+    DimensionsTooLarge {
+        rows: usize,
+        cols: usize,
+        max_rows: usize,
+        max_cols: usize,
+    },
+    #[error("seed is {seed_rows}x{seed_cols}, larger than the requested {rows}x{cols}")]
+    ExceedsDimensions {
+        seed_rows: usize,
+        seed_cols: usize,
+        rows: usize,
+        cols: usize,
+    },
+    #[error("row {row} has {found} cells, expected {expected} like the first row")]
+    RaggedRows {
+        expected: usize,
+        found: usize,
+        row: usize,
+    },
+    #[error("invalid packed board: {0}")]
+    InvalidPacked(String),
+    #[error("coordinate ({x}, {y}) is outside the {rows}x{cols} board")]
+    CoordinateOutOfBounds {
+        x: i32,
+        y: i32,
+        rows: usize,
+        cols: usize,
+    },
+}
+
+impl BoardError {
+    /// The HTTP status handlers should respond with for this error.
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            BoardError::InvalidSeparator(_)
+            | BoardError::InvalidSeedCharacter { .. }
+            | BoardError::InvalidMacrocell(_)
+            | BoardError::EmptySeed
+            | BoardError::InvalidRleHeader(_)
+            | BoardError::UnexpectedRleTag(_)
+            | BoardError::InvalidLife106(_)
+            | BoardError::ExceedsDimensions { .. }
+            | BoardError::RaggedRows { .. }
+            | BoardError::InvalidPacked(_)
+            | BoardError::CoordinateOutOfBounds { .. } => StatusCode::BAD_REQUEST,
+            BoardError::TooLarge { .. } | BoardError::DimensionsTooLarge { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+        }
+    }
 }
 
 pub const ALIVE: char = '#';
 pub const DEAD: char = '.';
 pub const SEPARATOR: char = '\n';
+pub const MAX_CELLS: usize = 1_000_000;
+// default limits of a seed's rows and columns, see SeedOptions
+pub const MAX_ROWS: usize = 1_000;
+pub const MAX_COLS: usize = 1_000;
+// boards with fewer live cells than this share step as a SparseBoard
+const SPARSE_DENSITY: f64 = 0.02;
+// boards with at least this many cells step their rows in parallel with the
+// rayon feature, smaller ones aren't worth the threads
+#[cfg(feature = "rayon")]
+const PARALLEL_CELLS: usize = 1 << 16;
 
 const NEIGHBORS: [(isize, isize); 8] = [
     (-1, -1), // NW
@@ -24,182 +123,2915 @@ const NEIGHBORS: [(isize, isize); 8] = [
     (0, -1),  // W
 ];
 
-#[derive(Serialize, Deserialize)]
+#[derive(Debug)]
+pub struct SeedOptions {
+    pub alive: char,
+    pub dead: char,
+    pub separator: char,
+    pub trim: bool,
+    pub lenient: bool,
+    pub rows: Option<usize>,
+    pub cols: Option<usize>,
+    /// Reject rows of differing lengths instead of padding the short ones
+    /// with dead cells.
+    pub strict_dimensions: bool,
+    /// Most rows and columns the board may have, explicit dimensions
+    /// included.
+    pub max_rows: usize,
+    pub max_cols: usize,
+}
+
+impl SeedOptions {
+    pub fn new(
+        alive: Option<char>,
+        dead: Option<char>,
+        separator: Option<char>,
+        trim: Option<bool>,
+        lenient: Option<bool>,
+    ) -> Self {
+        Self {
+            alive: alive.unwrap_or(ALIVE),
+            dead: dead.unwrap_or(DEAD),
+            separator: separator.unwrap_or(SEPARATOR),
+            trim: trim.unwrap_or(true),
+            lenient: lenient.unwrap_or(false),
+            rows: None,
+            cols: None,
+            strict_dimensions: false,
+            max_rows: MAX_ROWS,
+            max_cols: MAX_COLS,
+        }
+    }
+
+    /// Guesses the row separator of a pasted seed. Any line break wins (a
+    /// `\r` before it is dropped by [`Board::from_seed`]), otherwise it is the
+    /// first non-cell character that splits the seed into rows of one equal,
+    /// non-zero width. Falls back to `\n`.
+    pub fn detect_separator(&mut self, seed: &str) {
+        let (alive, dead) = (self.alive, self.dead);
+        let seed = trim_rows(seed, SEPARATOR, |c| {
+            c.is_whitespace() && c != alive && c != dead
+        });
+
+        if alive != SEPARATOR && dead != SEPARATOR && seed.contains(SEPARATOR) {
+            self.separator = SEPARATOR;
+            return;
+        }
+
+        let mut candidates: Vec<char> = Vec::new();
+        for c in seed.chars() {
+            if c != alive && c != dead && !candidates.contains(&c) {
+                candidates.push(c);
+            }
+        }
+
+        self.separator = candidates
+            .into_iter()
+            .find(|&c| {
+                let mut widths = seed.split(c).map(|row| row.chars().count());
+                let first = widths.next().unwrap_or(0);
+                first > 0 && widths.all(|w| w == first)
+            })
+            .unwrap_or(SEPARATOR);
+    }
+}
+
+impl Default for SeedOptions {
+    fn default() -> Self {
+        Self::new(None, None, None, None, None)
+    }
+}
+
+/// Drops rows of only `blank` chars from both ends of a seed, and blank
+/// chars after its last row. Leading whitespace of the first row is kept,
+/// it may be dead cells.
+fn trim_rows(seed: &str, separator: char, blank: impl Fn(char) -> bool) -> &str {
+    let rows = seed.split(separator).collect::<Vec<_>>();
+    let is_blank = |row: &&str| row.chars().all(&blank);
+    let (Some(first), Some(last)) = (
+        rows.iter().position(|row| !is_blank(row)),
+        rows.iter().rposition(|row| !is_blank(row)),
+    ) else {
+        return "";
+    };
+
+    let len = |rows: &[&str]| {
+        rows.iter()
+            .map(|row| row.len() + separator.len_utf8())
+            .sum::<usize>()
+    };
+    let start = len(&rows[..first]);
+    let end = len(&rows[..=last]) - separator.len_utf8();
+    seed[start..end].trim_end_matches(blank)
+}
+
+/// Neighbor counts on which dead cells are born and live cells survive, in
+/// B/S notation like `B3/S23` (Conway's Life, the default).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rule {
+    pub birth: HashSet<u8>,
+    pub survival: HashSet<u8>,
+}
+
+impl Rule {
+    pub fn parse(rule: &str) -> Result<Rule, RuleError> {
+        let invalid = || RuleError::InvalidFormat(rule.to_string());
+        let (birth, survival) = rule.trim().split_once('/').ok_or_else(invalid)?;
+        let birth = birth.strip_prefix(['B', 'b']).ok_or_else(invalid)?;
+        let survival = survival.strip_prefix(['S', 's']).ok_or_else(invalid)?;
+
+        let counts = |digits: &str| {
+            digits
+                .chars()
+                .map(|c| match c.to_digit(10) {
+                    Some(n) if n <= 8 => Ok(n as u8),
+                    _ => Err(RuleError::InvalidCount(c)),
+                })
+                .collect::<Result<HashSet<u8>, RuleError>>()
+        };
+
+        Ok(Rule {
+            birth: counts(birth)?,
+            survival: counts(survival)?,
+        })
+    }
+
+    fn sorted(counts: &HashSet<u8>) -> Vec<u8> {
+        let mut counts = counts.iter().copied().collect::<Vec<u8>>();
+        counts.sort_unstable();
+        counts
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Rule {
+            birth: HashSet::from([3]),
+            survival: HashSet::from([2, 3]),
+        }
+    }
+}
+
+impl std::fmt::Display for Rule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = |counts| {
+            Rule::sorted(counts)
+                .iter()
+                .map(u8::to_string)
+                .collect::<String>()
+        };
+        write!(f, "B{}/S{}", digits(&self.birth), digits(&self.survival))
+    }
+}
+
+// sets don't hash, so hash the sorted counts
+impl Hash for Rule {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        Rule::sorted(&self.birth).hash(state);
+        Rule::sorted(&self.survival).hash(state);
+    }
+}
+
+// stored as its rulestring
+impl Serialize for Rule {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Rule {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rule = String::deserialize(deserializer)?;
+        Rule::parse(&rule).map_err(serde::de::Error::custom)
+    }
+}
+
+/// What lies past the edges of a board.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum Boundary {
+    /// Cells off the board are always dead.
+    #[default]
+    Dead,
+    /// The board wraps around, opposite edges are neighbors.
+    Toroidal,
+    /// Cells off the board are dead, but the board grows a row or column
+    /// wherever live cells reach its edge, so patterns aren't clipped.
+    Expanding,
+}
+
+/// Axis a board is mirrored across by [`Board::mirror`].
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Axis {
+    /// Left and right swap.
+    Horizontal,
+    /// Top and bottom swap.
+    Vertical,
+    /// Both at once, the same as rotating by 180°.
+    Both,
+}
+
+/// Order cells are visited in when updating a board in place.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum UpdateOrder {
+    /// Row by row, left to right.
+    Sequential,
+    /// A shuffled order drawn from the rng.
+    Random,
+}
+
+#[derive(Serialize, Deserialize, Hash, Clone)]
 pub struct Game {
     pub board: Board,
     pub generation: usize,
     pub delta: usize,
+    /// The board at generation 0, missing for games stored before it was
+    /// kept until they are stepped again.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<Board>,
+    /// Unix seconds the game was created at, missing for games stored before
+    /// it was kept.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<u64>,
+    /// Unix seconds from which the game is gone, if it expires.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<u64>,
 }
 
 impl From<Board> for Game {
     fn from(board: Board) -> Self {
         Game {
+            seed: Some(board.clone()),
             board,
             generation: 0,
             delta: 0,
+            created_at: None,
+            expires_at: None,
         }
     }
 }
 
 impl Game {
+    /// Keeps the current board as the seed if the game has none, so older
+    /// games can be reset to where they were before they next change.
+    pub fn keep_seed(&mut self) {
+        self.seed.get_or_insert_with(|| self.board.clone());
+    }
+
+    /// Rewinds the game to generation 0 with its seed.
+    pub fn reset(&mut self) {
+        self.keep_seed();
+        self.board = self.seed.clone().expect("seed was just kept");
+        self.generation = 0;
+        self.delta = 0;
+    }
+
     pub fn next(&mut self) {
         self.delta = self.board.next() as usize;
         self.generation += 1;
     }
 
-    pub fn is_terminal(&self) -> bool {
-        self.generation != 0 && self.delta == 0
+    pub fn next_noisy(&mut self, p: f64, rng: &mut impl Rng) {
+        self.delta = self.board.next_noisy(p, rng) as usize;
+        self.generation += 1;
     }
-}
 
-impl std::fmt::Debug for Game {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "[n: {}, Δ: {}] \n", self.generation, self.delta,)?;
-        write!(f, "{}", self.board)
+    pub fn next_async(&mut self, order: UpdateOrder, rng: &mut impl Rng) {
+        self.delta = self.board.next_async(order, rng) as usize;
+        self.generation += 1;
     }
-}
 
-#[derive(Serialize, Deserialize)]
-pub struct Board {
-    pub grid: Vec<Vec<bool>>,
-}
+    /// Whether the game has expired at `now`, in unix seconds.
+    pub fn is_expired(&self, now: u64) -> bool {
+        self.expires_at.is_some_and(|at| at <= now)
+    }
 
-impl TryFrom<String> for Board {
-    type Error = BoardError;
+    pub fn is_terminal(&self) -> bool {
+        self.generation != 0 && self.delta == 0
+    }
 
-    fn try_from(value: String) -> Result<Self, Self::Error> {
-        Board::from_seed(value, None, None, None)
+    /// Steps exactly `n` generations.
+    pub fn step_n(&mut self, n: usize) -> StepReport {
+        self.step_while(n, |_| true)
     }
-}
 
-impl std::fmt::Display for Board {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.stringify(None, None, None))
+    /// Steps until the board stops changing or `max` generations have passed.
+    /// Unlike [`Game::run_to_terminal`] it doesn't stop at oscillators.
+    pub fn run_until_terminal(&mut self, max: usize) -> StepReport {
+        self.step_while(max, |game| !game.is_terminal())
     }
-}
 
-impl Board {
-    pub fn new(grid: Vec<Vec<bool>>) -> Self {
-        Board { grid }
+    /// Steps up to `max` generations while `keep_going` holds before each.
+    fn step_while(&mut self, max: usize, keep_going: impl Fn(&Game) -> bool) -> StepReport {
+        let was_terminal = self.is_terminal();
+        let mut report = StepReport {
+            generations: 0,
+            total_delta: 0,
+            became_terminal: false,
+        };
+
+        while report.generations < max && keep_going(self) {
+            self.next();
+            report.generations += 1;
+            report.total_delta += self.delta;
+        }
+
+        report.became_terminal = !was_terminal && self.is_terminal();
+        report
     }
 
-    pub fn from_seed(
-        seed: String,
-        alive: Option<char>,
-        dead: Option<char>,
-        separator: Option<char>,
-    ) -> Result<Self, BoardError> {
-        let alive = alive.unwrap_or(ALIVE);
-        let dead = dead.unwrap_or(DEAD);
-        let separator = separator.unwrap_or(SEPARATOR);
+    /// Steps until the game is terminal, enters a cycle, or `max` generations
+    /// have passed. The period is the cycle length, if one was found.
+    pub fn run_to_terminal(&mut self, max: usize) -> RunReport {
+        let mut cycles = CycleDetector::new(max + 1);
+        cycles.observe(self);
+        let mut period = None;
 
-        if separator == alive || separator == dead {
-            return Err(BoardError::InvalidSeparator(separator));
+        for _ in 0..max {
+            self.next();
+            period = cycles.observe(self);
+            if period.is_some() {
+                break;
+            }
         }
 
-        let seeds = seed.trim().split(separator).collect::<Vec<&str>>();
-        let cols = seeds.iter().map(|s| s.len()).max().unwrap_or(0);
+        self.report(period)
+    }
 
-        let mut grid = vec![vec![false; cols]; seeds.len()];
-        for (row_idx, row_seed) in seeds.into_iter().enumerate() {
-            for (col_idx, cell) in row_seed.char_indices() {
-                if cell == alive {
-                    grid[row_idx][col_idx] = true;
-                } else if cell != dead {
-                    return Err(BoardError::InvalidSeedCharacter(cell, alive, dead));
-                }
+    /// Steps a copy of the game up to `max_lookback` generations looking for
+    /// a board it has already seen, returning the period of the cycle it ends
+    /// up in. Still lifes have a period of 1.
+    pub fn detect_period(&self, max_lookback: usize) -> Option<usize> {
+        let mut game = self.clone();
+        let mut cycles = CycleDetector::new(max_lookback + 1);
+        cycles.observe(&game);
+
+        for _ in 0..max_lookback {
+            game.next();
+            if let Some(period) = cycles.observe(&game) {
+                return Some(period);
             }
         }
 
-        Ok(Board { grid })
+        None
     }
 
-    pub fn stringify(
-        &self,
-        alive: Option<char>,
-        dead: Option<char>,
-        separator: Option<char>,
-    ) -> String {
-        let alive = alive.unwrap_or(ALIVE);
-        let dead = dead.unwrap_or(DEAD);
-        let separator = separator.unwrap_or(SEPARATOR);
+    pub fn report(&self, period: Option<usize>) -> RunReport {
+        RunReport {
+            generation: self.generation,
+            delta: self.delta,
+            terminal: self.is_terminal(),
+            period,
+        }
+    }
 
-        let mut result = String::with_capacity(self.rows() * self.cols() + self.rows());
+    /// Steps up to `steps` generations looking for the live cells to die out
+    /// or repeat their shape, either in place or shifted. Otherwise the game
+    /// is growing if a least squares fit of the population slopes upward.
+    pub fn classify(&mut self, steps: usize) -> Classification {
+        let mut shapes: HashMap<u64, (usize, i64, i64)> = HashMap::new();
+        let mut populations = Vec::with_capacity(steps + 1);
+        // expanding boards grow on the top and left too, moving every cell
+        // without it having moved, so positions are kept from the start
+        let mut origin = (0i64, 0i64);
+
+        for step in 0..=steps {
+            if step > 0 {
+                if let Some((top, _, left, _)) = self.board.expansion() {
+                    origin.0 -= top as i64;
+                    origin.1 -= left as i64;
+                }
+                self.next();
+            }
 
-        for (i, row) in self.grid.iter().enumerate() {
-            for cell in row {
-                result.push(if *cell { alive } else { dead });
+            let Some((row, col, rows, cols)) = self.board.live_bounds() else {
+                return Classification::Extinct {
+                    generation: self.generation,
+                };
+            };
+            populations.push(self.board.population() as f64);
+
+            let shape = self.board.region(row, col, rows, cols).fingerprint();
+            let (row, col) = (row as i64 + origin.0, col as i64 + origin.1);
+            if let Some((generation, prev_row, prev_col)) =
+                shapes.insert(shape, (self.generation, row, col))
+            {
+                let period = self.generation - generation;
+                let dx = col - prev_col;
+                let dy = row - prev_row;
+                return match (dx, dy) {
+                    (0, 0) if period == 1 => Classification::StillLife,
+                    (0, 0) => Classification::Oscillator { period },
+                    _ => Classification::Spaceship { period, dx, dy },
+                };
             }
-            if i < self.rows() - 1 {
-                result.push(separator);
+        }
+
+        let n = populations.len() as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = populations.iter().sum::<f64>() / n;
+        let slope = populations
+            .iter()
+            .enumerate()
+            .map(|(x, y)| (x as f64 - mean_x) * (y - mean_y))
+            .sum::<f64>();
+
+        if slope > 0.0 {
+            Classification::Growing
+        } else {
+            Classification::Undetermined
+        }
+    }
+
+    /// Steps `steps` generations, recording each delta along with its moving
+    /// average over the last `window` generations.
+    pub fn activity(&mut self, steps: usize, window: usize) -> Vec<Activity> {
+        let mut recent = VecDeque::with_capacity(window);
+        let mut series = Vec::with_capacity(steps);
+
+        for _ in 0..steps {
+            self.next();
+            if recent.len() == window {
+                recent.pop_front();
             }
+            recent.push_back(self.delta);
+            series.push(Activity {
+                generation: self.generation,
+                delta: self.delta,
+                average: recent.iter().sum::<usize>() as f64 / recent.len() as f64,
+            });
         }
 
-        result
+        series
     }
 
-    pub fn next(&mut self) -> i32 {
-        let mut next = self.grid.clone();
-        let mut delta = 0;
+    /// Records the population of the current generation and of up to
+    /// `steps` more, stopping early once the game is terminal. Returns the
+    /// series and whether it stopped early.
+    pub fn population_series(&mut self, steps: usize) -> (Vec<Population>, bool) {
+        let mut series = Vec::with_capacity(steps + 1);
+        series.push(Population::from(&*self));
 
-        for row in 0..self.grid.len() {
-            for col in 0..self.grid[row].len() {
-                let (next_state, has_changed) = self.interact(row, col);
-                if has_changed {
-                    delta += 1;
-                }
-                next[row][col] = next_state
+        for _ in 0..steps {
+            if self.is_terminal() {
+                return (series, true);
             }
+            self.next();
+            series.push(Population::from(&*self));
         }
 
-        self.grid = next;
-        delta
+        (series, false)
     }
+}
 
-    pub fn rows(&self) -> usize {
-        self.grid.len()
+/// What a pattern turned out to be when stepped for a while.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+#[serde(tag = "class", rename_all = "kebab-case")]
+pub enum Classification {
+    /// Every cell died by `generation`.
+    Extinct {
+        generation: usize,
+    },
+    StillLife,
+    Oscillator {
+        period: usize,
+    },
+    /// The pattern reappears every `period` generations moved by `dx`
+    /// columns and `dy` rows.
+    Spaceship {
+        period: usize,
+        dx: i64,
+        dy: i64,
+    },
+    /// The population trends upward without repeating.
+    Growing,
+    /// None of the above within the generations stepped.
+    Undetermined,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Activity {
+    pub generation: usize,
+    pub delta: usize,
+    pub average: f64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct Population {
+    pub generation: usize,
+    pub population: usize,
+    pub delta: usize,
+}
+
+impl From<&Game> for Population {
+    fn from(game: &Game) -> Self {
+        Population {
+            generation: game.generation,
+            population: game.board.population(),
+            delta: game.delta,
+        }
     }
+}
 
-    pub fn cols(&self) -> usize {
-        self.grid[0].len()
+#[derive(Serialize, Debug)]
+pub struct RunReport {
+    pub generation: usize,
+    pub delta: usize,
+    pub terminal: bool,
+    pub period: Option<usize>,
+}
+
+/// What a run of [`Game::step_n`] or [`Game::run_until_terminal`] did.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StepReport {
+    /// Generations stepped.
+    pub generations: usize,
+    /// Cells changed, summed over every generation stepped.
+    pub total_delta: usize,
+    /// Whether the game was terminal after the last step, but not before the
+    /// first.
+    pub became_terminal: bool,
+}
+
+/// The cells a step changed, each as `(row, col)` in row-major order.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Delta {
+    pub born: Vec<(usize, usize)>,
+    pub died: Vec<(usize, usize)>,
+}
+
+impl Delta {
+    /// How many cells changed, the `delta` of the step.
+    pub fn len(&self) -> usize {
+        self.born.len() + self.died.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.born.is_empty() && self.died.is_empty()
+    }
+}
+
+/// Spots a board coming back by remembering the fingerprints of the last
+/// `window` boards it was shown, so only periods up to `window` are found.
+pub struct CycleDetector {
+    window: usize,
+    seen: HashMap<u64, usize>,
+    recent: VecDeque<u64>,
+}
+
+impl CycleDetector {
+    pub fn new(window: usize) -> Self {
+        Self {
+            window: window.max(1),
+            seen: HashMap::new(),
+            recent: VecDeque::new(),
+        }
     }
 
-    fn safe_get(&self, row: isize, col: isize) -> bool {
-        if row < 0 || col < 0 {
-            return false;
+    /// Records the game's board, returning the period if it was seen before.
+    pub fn observe(&mut self, game: &Game) -> Option<usize> {
+        let fingerprint = game.board.fingerprint();
+        if let Some(prev) = self.seen.get(&fingerprint) {
+            return Some(game.generation - prev);
         }
 
-        if let Some(r) = self.grid.get(row as usize) {
-            if let Some(cell) = r.get(col as usize) {
-                return *cell;
+        if self.recent.len() == self.window {
+            if let Some(oldest) = self.recent.pop_front() {
+                self.seen.remove(&oldest);
             }
         }
+        self.recent.push_back(fingerprint);
+        self.seen.insert(fingerprint, game.generation);
+        None
+    }
+}
 
-        false
+impl std::fmt::Debug for Game {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "[n: {}, Δ: {}] ", self.generation, self.delta)?;
+        write!(f, "{}", self.board)
     }
+}
 
-    fn interact(&self, row: usize, col: usize) -> (bool, bool) {
-        let neighbors = self.neighbors(row, col);
-        let alive = self.safe_get(row as isize, col as isize);
+/// A node of a macrocell quadtree, `live` is set if any cell beneath it is alive.
+enum MacroNode {
+    Empty,
+    Leaf {
+        rows: [u8; 8],
+        live: bool,
+    },
+    Branch {
+        level: usize,
+        children: [usize; 4],
+        live: bool,
+    },
+}
+
+impl MacroNode {
+    fn level(&self) -> Option<usize> {
+        match self {
+            MacroNode::Empty => None,
+            MacroNode::Leaf { .. } => Some(3),
+            MacroNode::Branch { level, .. } => Some(*level),
+        }
+    }
 
-        let next = match (neighbors, alive) {
-            // Any dead cell with exactly three live neighbors becomes a live cell, as if by reproduction.
-            (3, false) => true,
-            // Any live cell with fewer than two live neighbors dies.
-            (0..=1, true) => false,
-            // Any live cell with two or three live neighbors lives on to the next generation.
-            (2..=3, true) => true,
-            // Any live cell with more than three live neighbors dies. Or, a dead cell stays dead.
-            (_, _) => false,
+    fn is_live(&self) -> bool {
+        match self {
+            MacroNode::Empty => false,
+            MacroNode::Leaf { live, .. } | MacroNode::Branch { live, .. } => *live,
+        }
+    }
+}
+
+#[derive(Deserialize, Clone)]
+#[serde(try_from = "StoredBoard")]
+pub struct Board {
+    pub grid: Vec<Vec<bool>>,
+    pub rule: Rule,
+    pub boundary: Boundary,
+    /// How many generations each live cell has been alive for, 0 for dead
+    /// cells. Boards saved before ages existed start counting on their next
+    /// step, and ages are stored up to 255.
+    pub age: Vec<Vec<u32>>,
+    scratch: Scratch,
+}
+
+// ages and the scratch buffer don't change what the board is, so they're left
+// out of fingerprints
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.grid.hash(state);
+        self.rule.hash(state);
+        self.boundary.hash(state);
+    }
+}
+
+/// Checks requested dimensions fit in [`MAX_CELLS`]. Each side is checked on
+/// its own too, as with an empty side any product fits, but allocating the
+/// other side may not.
+fn fits(rows: usize, cols: usize) -> Result<(), BoardError> {
+    if rows > MAX_CELLS || cols > MAX_CELLS || rows * cols > MAX_CELLS {
+        return Err(BoardError::TooLarge {
+            rows,
+            cols,
+            max: MAX_CELLS,
+        });
+    }
+    Ok(())
+}
+
+/// How a board is stored, the grid and ages packed and base64 encoded.
+#[derive(Deserialize)]
+struct StoredBoard {
+    #[serde(deserialize_with = "unpack_grid")]
+    grid: Vec<Vec<bool>>,
+    // boards saved before rules existed are conway
+    #[serde(default)]
+    rule: Rule,
+    #[serde(default)]
+    boundary: Boundary,
+    #[serde(default)]
+    age: Option<StoredAges>,
+}
+
+impl Serialize for Board {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("Board", 4)?;
+        state.serialize_field("grid", &STANDARD.encode(pack(&self.grid)))?;
+        state.serialize_field("rule", &self.rule)?;
+        state.serialize_field("boundary", &self.boundary)?;
+        if self.has_age() {
+            state.serialize_field("age", &STANDARD.encode(self.pack_ages()))?;
+        } else {
+            state.skip_field("age")?;
+        }
+        state.end()
+    }
+}
+
+impl TryFrom<StoredBoard> for Board {
+    type Error = base64::DecodeError;
+
+    fn try_from(stored: StoredBoard) -> Result<Self, Self::Error> {
+        let age = match stored.age {
+            Some(StoredAges::Packed(packed)) => {
+                unpack_ages(&stored.grid, &STANDARD.decode(packed)?)
+            }
+            Some(StoredAges::Cells(age)) => age,
+            None => Vec::new(),
         };
+        Ok(Board {
+            rule: stored.rule,
+            boundary: stored.boundary,
+            age,
+            ..Board::new(stored.grid)
+        })
+    }
+}
 
-        (next, next != alive)
+/// Grids are stored as base64 of [`Board::to_packed`]. Boards stored before
+/// that have the grid as arrays of bools, which are still read and are packed
+/// the next time the game is saved.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredGrid {
+    Packed(String),
+    Bools(Vec<Vec<bool>>),
+}
+
+/// Ages are stored as one byte for each live cell, row by row, so dead cells
+/// cost nothing. Boards stored before that have the age of every cell.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum StoredAges {
+    Packed(String),
+    Cells(Vec<Vec<u32>>),
+}
+
+/// Spreads the ages of live cells back over the grid. Ages that don't match
+/// the live cells are dropped and start counting again.
+fn unpack_ages(grid: &[Vec<bool>], packed: &[u8]) -> Vec<Vec<u32>> {
+    let live = grid.iter().flatten().filter(|cell| **cell).count();
+    if packed.len() != live {
+        return Vec::new();
     }
 
-    fn neighbors(&self, row: usize, col: usize) -> usize {
-        NEIGHBORS
+    let mut ages = packed.iter();
+    grid.iter()
+        .map(|cells| {
+            cells
+                .iter()
+                .map(|cell| match cell {
+                    true => ages.next().map_or(1, |age| *age as u32),
+                    false => 0,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn unpack_grid<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Vec<Vec<bool>>, D::Error> {
+    match StoredGrid::deserialize(deserializer)? {
+        StoredGrid::Packed(packed) => {
+            let bytes = STANDARD.decode(packed).map_err(serde::de::Error::custom)?;
+            unpack(&bytes).map_err(serde::de::Error::custom)
+        }
+        StoredGrid::Bools(grid) => Ok(rectangular(grid)),
+    }
+}
+
+/// Pads short rows with dead cells to the widest row, so `rows()` and
+/// `cols()` describe every cell.
+fn rectangular(mut grid: Vec<Vec<bool>>) -> Vec<Vec<bool>> {
+    let cols = grid.iter().map(Vec::len).max().unwrap_or(0);
+    for row in &mut grid {
+        row.resize(cols, false);
+    }
+    grid
+}
+
+/// Big-endian `u32` rows and cols, then the cells row by row, eight to a byte
+/// starting from the high bit, with the last byte padded with dead cells.
+/// The dimensions are stored rather than implied by the cells, so dead rows
+/// and columns at the edges survive. Short rows are padded to the widest.
+fn pack(grid: &[Vec<bool>]) -> Vec<u8> {
+    let rows = grid.len();
+    let cols = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let cells = grid
+        .iter()
+        .flat_map(|row| {
+            row.iter()
+                .copied()
+                .chain(std::iter::repeat(false))
+                .take(cols)
+        })
+        .collect::<Vec<_>>();
+
+    let mut packed = Vec::with_capacity(8 + cells.len().div_ceil(8));
+    packed.extend_from_slice(&(rows as u32).to_be_bytes());
+    packed.extend_from_slice(&(cols as u32).to_be_bytes());
+    for chunk in cells.chunks(8) {
+        let byte = chunk
             .iter()
-            .filter(|(r, c)| self.safe_get(row as isize + r, col as isize + c))
-            .count()
+            .enumerate()
+            .fold(0u8, |byte, (i, alive)| byte | ((*alive as u8) << (7 - i)));
+        packed.push(byte);
+    }
+    packed
+}
+
+fn unpack(packed: &[u8]) -> Result<Vec<Vec<bool>>, BoardError> {
+    let invalid = |reason: &str| BoardError::InvalidPacked(reason.to_string());
+    let (Some(rows), Some(cols)) = (packed.get(0..4), packed.get(4..8)) else {
+        return Err(invalid("missing dimensions"));
+    };
+    let rows = u32::from_be_bytes(rows.try_into().unwrap()) as usize;
+    let cols = u32::from_be_bytes(cols.try_into().unwrap()) as usize;
+    // each side is checked on its own, a board of no cells can still have
+    // billions of empty rows to allocate
+    fits(rows, cols)?;
+    let cells = rows * cols;
+
+    let bits = &packed[8..];
+    if bits.len() != cells.div_ceil(8) {
+        return Err(invalid(&format!(
+            "expected {} bytes of cells for {}x{}, found {}",
+            cells.div_ceil(8),
+            rows,
+            cols,
+            bits.len()
+        )));
+    }
+
+    let cell = |i: usize| bits[i / 8] & (1 << (7 - i % 8)) != 0;
+    Ok((0..rows)
+        .map(|row| (0..cols).map(|col| cell(row * cols + col)).collect())
+        .collect())
+}
+
+/// The buffer the next generation is written to before it's swapped with the
+/// grid, kept between steps to avoid allocating a grid each one. It is not
+/// part of the board, so it's not cloned.
+#[derive(Default)]
+struct Scratch(Vec<Vec<bool>>);
+
+impl Clone for Scratch {
+    fn clone(&self) -> Self {
+        Scratch::default()
+    }
+}
+
+impl TryFrom<String> for Board {
+    type Error = BoardError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Board::from_seed(value, Default::default())
+    }
+}
+
+impl std::fmt::Display for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.stringify(None, None, None))
+    }
+}
+
+impl Board {
+    pub fn new(grid: Vec<Vec<bool>>) -> Self {
+        Board {
+            grid,
+            rule: Rule::default(),
+            boundary: Boundary::default(),
+            age: Vec::new(),
+            scratch: Scratch::default(),
+        }
+    }
+
+    /// Parses rows of `alive` and `dead` characters split by `separator`. The
+    /// board is as wide as the longest row, shorter rows are padded with dead
+    /// cells unless `strict_dimensions` is set.
+    pub fn from_seed(seed: String, opts: SeedOptions) -> Result<Self, BoardError> {
+        let SeedOptions {
+            alive,
+            dead,
+            separator,
+            trim,
+            lenient,
+            rows,
+            cols,
+            strict_dimensions,
+            max_rows,
+            max_cols,
+        } = opts;
+
+        if separator == alive || separator == dead {
+            return Err(BoardError::InvalidSeparator(separator));
+        }
+
+        // whitespace only counts as content when it is a cell character
+        let blank = |c: char| c.is_whitespace() && c != alive && c != dead;
+        if seed.chars().all(blank) {
+            return Err(BoardError::EmptySeed);
+        }
+
+        // trimming drops blank border rows, disable it to keep exact dimensions
+        let seed = if trim {
+            trim_rows(&seed, separator, blank)
+        } else {
+            seed.as_str()
+        };
+        // tolerate windows line endings unless '\r' is a cell character
+        let strip_cr = separator == '\n' && alive != '\r' && dead != '\r';
+        let too_large = |rows, cols| BoardError::DimensionsTooLarge {
+            rows,
+            cols,
+            max_rows,
+            max_cols,
+        };
+        // counted before the rows are collected so huge seeds fail cheaply
+        let seed_rows = seed.split(separator).count();
+        if seed_rows > max_rows {
+            let seed_cols = seed.split(separator).map(|s| s.chars().count()).max();
+            return Err(too_large(seed_rows, seed_cols.unwrap_or(0)));
+        }
+
+        let seeds = seed
+            .split(separator)
+            .map(|s| match s.strip_suffix('\r') {
+                Some(s) if strip_cr => s,
+                _ => s,
+            })
+            .collect::<Vec<&str>>();
+        let seed_cols = seeds.iter().map(|s| s.chars().count()).max().unwrap_or(0);
+
+        // otherwise rows shorter than the longest are padded with dead cells
+        if strict_dimensions {
+            let expected = seeds[0].chars().count();
+            let ragged = seeds
+                .iter()
+                .map(|s| s.chars().count())
+                .enumerate()
+                .find(|(_, found)| *found != expected);
+            if let Some((row, found)) = ragged {
+                return Err(BoardError::RaggedRows {
+                    expected,
+                    found,
+                    row,
+                });
+            }
+        }
+
+        // explicit dimensions must fit the seed, which is placed top left
+        let rows = rows.unwrap_or(seed_rows);
+        let cols = cols.unwrap_or(seed_cols);
+        if seed_rows > rows || seed_cols > cols {
+            return Err(BoardError::ExceedsDimensions {
+                seed_rows,
+                seed_cols,
+                rows,
+                cols,
+            });
+        }
+
+        if rows > max_rows || cols > max_cols {
+            return Err(too_large(rows, cols));
+        }
+
+        if rows.checked_mul(cols).is_none_or(|n| n > MAX_CELLS) {
+            return Err(BoardError::TooLarge {
+                rows,
+                cols,
+                max: MAX_CELLS,
+            });
+        }
+
+        let mut grid = vec![vec![false; cols]; rows];
+        for (row_idx, row_seed) in seeds.into_iter().enumerate() {
+            for (col_idx, cell) in row_seed.chars().enumerate() {
+                if cell == alive {
+                    grid[row_idx][col_idx] = true;
+                } else if cell != dead && !lenient {
+                    return Err(BoardError::InvalidSeedCharacter {
+                        found: cell,
+                        alive,
+                        dead,
+                        row: row_idx,
+                        col: col_idx,
+                    });
+                }
+            }
+        }
+
+        Ok(Board::new(grid))
+    }
+
+    /// Builds a `rows` x `cols` board from `(x, y)` offsets of live cells
+    /// relative to the center cell, with `x` growing right and `y` down.
+    pub fn from_centered_coords(
+        coords: &[(i32, i32)],
+        rows: usize,
+        cols: usize,
+    ) -> Result<Self, BoardError> {
+        fits(rows, cols)?;
+
+        let mut grid = vec![vec![false; cols]; rows];
+        let (center_row, center_col) = (rows as i64 / 2, cols as i64 / 2);
+        for &(x, y) in coords {
+            let (row, col) = (center_row + y as i64, center_col + x as i64);
+            if row < 0 || col < 0 || row >= rows as i64 || col >= cols as i64 {
+                return Err(BoardError::CoordinateOutOfBounds { x, y, rows, cols });
+            }
+            grid[row as usize][col as usize] = true;
+        }
+
+        Ok(Board::new(grid))
+    }
+
+    /// Builds a `rows` x `cols` board where each cell is alive with
+    /// probability `density`.
+    pub fn random(
+        rows: usize,
+        cols: usize,
+        density: f64,
+        rng: &mut impl Rng,
+    ) -> Result<Self, BoardError> {
+        fits(rows, cols)?;
+
+        let grid = (0..rows)
+            .map(|_| (0..cols).map(|_| rng.gen_bool(density)).collect())
+            .collect();
+
+        Ok(Board::new(grid))
+    }
+
+    /// Parses a two-state RLE pattern: `#` comment lines, an
+    /// `x = cols, y = rows[, rule = B3/S23]` header, then runs of `b` (dead)
+    /// and `o` (alive) cells with `$` ending rows and `!` ending the pattern.
+    pub fn from_rle(input: &str) -> Result<Self, BoardError> {
+        let invalid = |reason: String| BoardError::InvalidRleHeader(reason);
+
+        let mut lines = input.lines().filter(|l| !l.trim_start().starts_with('#'));
+        let header = lines.next().ok_or_else(|| invalid("missing".to_string()))?;
+
+        let (mut cols, mut rows, mut rule) = (None, None, Rule::default());
+        for field in header.split(',') {
+            let (key, value) = field.split_once('=').ok_or_else(|| {
+                invalid(format!("expected 'key = value', got '{}'", field.trim()))
+            })?;
+            let (key, value) = (key.trim(), value.trim());
+            let size = || {
+                value
+                    .parse::<usize>()
+                    .map_err(|_| invalid(format!("invalid {}: '{}'", key, value)))
+            };
+            match key {
+                "x" => cols = Some(size()?),
+                "y" => rows = Some(size()?),
+                "rule" => rule = Rule::parse(value).map_err(|e| invalid(e.to_string()))?,
+                _ => return Err(invalid(format!("unknown key '{}'", key))),
+            }
+        }
+        let (Some(cols), Some(rows)) = (cols, rows) else {
+            return Err(invalid("x and y are required".to_string()));
+        };
+
+        fits(rows, cols)?;
+
+        let overflow = || invalid(format!("pattern doesn't fit in x = {}, y = {}", cols, rows));
+        let mut grid = vec![vec![false; cols]; rows];
+        let (mut row, mut col, mut count) = (0usize, 0usize, None::<usize>);
+        'body: for line in lines {
+            for c in line.chars() {
+                if let Some(digit) = c.to_digit(10) {
+                    count = Some(
+                        count
+                            .unwrap_or(0)
+                            .checked_mul(10)
+                            .and_then(|n| n.checked_add(digit as usize))
+                            .ok_or_else(overflow)?,
+                    );
+                    continue;
+                }
+
+                let run = count.take().unwrap_or(1);
+                match c {
+                    'b' | 'o' => {
+                        let end = col.checked_add(run).filter(|end| *end <= cols);
+                        let Some(end) = end.filter(|_| row < rows) else {
+                            return Err(overflow());
+                        };
+                        grid[row][col..end].fill(c == 'o');
+                        col = end;
+                    }
+                    '$' => {
+                        // a trailing `$` may end the last row
+                        row = row
+                            .checked_add(run)
+                            .filter(|row| *row <= rows)
+                            .ok_or_else(overflow)?;
+                        col = 0;
+                    }
+                    '!' => break 'body,
+                    c if c.is_whitespace() => {}
+                    _ => return Err(BoardError::UnexpectedRleTag(c)),
+                }
+            }
+        }
+
+        Ok(Board {
+            rule,
+            ..Board::new(grid)
+        })
+    }
+
+    /// Parses a plaintext (`.cells`) pattern: rows of `.` for dead and `O` for
+    /// alive cells, with `!` comment lines. Shorter rows are padded with dead
+    /// cells.
+    pub fn from_cells(input: &str) -> Result<Self, BoardError> {
+        let rows = input
+            .lines()
+            .filter(|l| !l.starts_with('!'))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        Board::from_seed(
+            rows,
+            SeedOptions::new(Some('O'), Some('.'), None, None, None),
+        )
+    }
+
+    /// Parses a Life 1.06 pattern: a `#Life 1.06` header, then the `x y`
+    /// coordinates of each live cell, one per line. The board is sized to the
+    /// bounding box of the cells with the smallest coordinates at the origin.
+    pub fn from_life106(input: &str) -> Result<Self, BoardError> {
+        let invalid = |reason: String| BoardError::InvalidLife106(reason);
+
+        let mut lines = input.lines().map(str::trim).filter(|l| !l.is_empty());
+        if lines.next() != Some("#Life 1.06") {
+            return Err(invalid("missing '#Life 1.06' header".to_string()));
+        }
+
+        let mut cells = Vec::new();
+        for line in lines.filter(|l| !l.starts_with('#')) {
+            let coords = line
+                .split_whitespace()
+                .map(|n| n.parse::<i64>())
+                .collect::<Result<Vec<_>, _>>();
+            match coords.as_deref() {
+                Ok(&[x, y]) => cells.push((x, y)),
+                _ => return Err(invalid(format!("expected 'x y', got '{}'", line))),
+            }
+        }
+
+        let (Some(min_x), Some(min_y)) = (
+            cells.iter().map(|(x, _)| *x).min(),
+            cells.iter().map(|(_, y)| *y).min(),
+        ) else {
+            return Err(BoardError::EmptySeed);
+        };
+        let max_x = cells.iter().map(|(x, _)| *x).max().unwrap_or(min_x);
+        let max_y = cells.iter().map(|(_, y)| *y).max().unwrap_or(min_y);
+
+        // widened so the span of far apart coordinates can't overflow
+        let span = |min: i64, max: i64| {
+            usize::try_from(max as i128 - min as i128 + 1).unwrap_or(usize::MAX)
+        };
+        let (rows, cols) = (span(min_y, max_y), span(min_x, max_x));
+        if rows.checked_mul(cols).is_none_or(|n| n > MAX_CELLS) {
+            return Err(BoardError::TooLarge {
+                rows,
+                cols,
+                max: MAX_CELLS,
+            });
+        }
+
+        let mut grid = vec![vec![false; cols]; rows];
+        for (x, y) in cells {
+            grid[(y - min_y) as usize][(x - min_x) as usize] = true;
+        }
+
+        Ok(Board::new(grid))
+    }
+
+    /// Parses a two-state Golly macrocell (`.mc`) pattern. The quadtree is
+    /// expanded into a dense board cropped to the bounding box of live cells,
+    /// which must fit in `MAX_CELLS`.
+    pub fn from_macrocell(input: &str) -> Result<Self, BoardError> {
+        let invalid = |idx: usize, reason: &str| {
+            BoardError::InvalidMacrocell(format!("line {}: {}", idx + 1, reason))
+        };
+
+        let mut lines = input.lines().enumerate();
+        match lines.next() {
+            Some((_, header)) if header.starts_with("[M2]") => {}
+            _ => return Err(invalid(0, "expected [M2] header")),
+        }
+
+        // node 0 is the empty node, the rest are numbered from 1 in file order
+        let mut nodes = vec![MacroNode::Empty];
+        for (idx, line) in lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if line.starts_with(['.', '*', '$']) {
+                let mut rows = [0u8; 8];
+                let (mut row, mut col) = (0, 0);
+                for c in line.chars() {
+                    match c {
+                        '$' => {
+                            row += 1;
+                            col = 0;
+                            continue;
+                        }
+                        '.' | '*' if row >= 8 || col >= 8 => {
+                            return Err(invalid(idx, "leaf exceeds 8x8 cells"))
+                        }
+                        '.' => {}
+                        '*' => rows[row] |= 1 << col,
+                        _ => return Err(invalid(idx, &format!("unexpected character '{}'", c))),
+                    }
+                    col += 1;
+                }
+                let live = rows.iter().any(|r| *r != 0);
+                nodes.push(MacroNode::Leaf { rows, live });
+                continue;
+            }
+
+            let fields = line
+                .split_whitespace()
+                .map(|f| f.parse::<usize>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| invalid(idx, "expected 'level nw ne sw se'"))?;
+            let (level, children) = match fields[..] {
+                [level, nw, ne, sw, se] => (level, [nw, ne, sw, se]),
+                _ => return Err(invalid(idx, "expected 'level nw ne sw se'")),
+            };
+            if !(4..=62).contains(&level) {
+                return Err(invalid(idx, &format!("unsupported node level {}", level)));
+            }
+
+            let mut live = false;
+            for child in children {
+                let node = nodes
+                    .get(child)
+                    .ok_or_else(|| invalid(idx, &format!("undefined node {}", child)))?;
+                if node.level().is_some_and(|l| l != level - 1) {
+                    return Err(invalid(idx, &format!("node {} has the wrong level", child)));
+                }
+                live |= node.is_live();
+            }
+            nodes.push(MacroNode::Branch {
+                level,
+                children,
+                live,
+            });
+        }
+
+        let root = nodes.len() - 1;
+        let size = nodes[root].level().map_or(0, |l| 1usize << l.min(40));
+        let too_large = BoardError::TooLarge {
+            rows: size,
+            cols: size,
+            max: MAX_CELLS,
+        };
+
+        let mut cells = Vec::new();
+        let mut stack = vec![(root, 0u64, 0u64)];
+        while let Some((id, row, col)) = stack.pop() {
+            match &nodes[id] {
+                MacroNode::Empty => {}
+                MacroNode::Leaf { rows, .. } => {
+                    for (r, bits) in rows.iter().enumerate() {
+                        for c in (0..8).filter(|c| bits & (1 << c) != 0) {
+                            cells.push((row + r as u64, col + c as u64));
+                        }
+                    }
+                    if cells.len() > MAX_CELLS {
+                        return Err(too_large);
+                    }
+                }
+                MacroNode::Branch {
+                    level, children, ..
+                } => {
+                    let half = 1u64 << (level - 1);
+                    let offsets = [(0, 0), (0, half), (half, 0), (half, half)];
+                    for (child, (dr, dc)) in children.iter().zip(offsets) {
+                        if nodes[*child].is_live() {
+                            stack.push((*child, row + dr, col + dc));
+                        }
+                    }
+                }
+            }
+        }
+
+        if cells.is_empty() {
+            return Err(BoardError::InvalidMacrocell(
+                "pattern has no live cells".to_string(),
+            ));
+        }
+
+        let min_row = cells.iter().map(|(r, _)| *r).min().unwrap_or(0);
+        let max_row = cells.iter().map(|(r, _)| *r).max().unwrap_or(0);
+        let min_col = cells.iter().map(|(_, c)| *c).min().unwrap_or(0);
+        let max_col = cells.iter().map(|(_, c)| *c).max().unwrap_or(0);
+        let rows = usize::try_from(max_row - min_row + 1).unwrap_or(usize::MAX);
+        let cols = usize::try_from(max_col - min_col + 1).unwrap_or(usize::MAX);
+        if rows.checked_mul(cols).is_none_or(|n| n > MAX_CELLS) {
+            return Err(BoardError::TooLarge {
+                rows,
+                cols,
+                max: MAX_CELLS,
+            });
+        }
+
+        let mut grid = vec![vec![false; cols]; rows];
+        for (row, col) in cells {
+            grid[(row - min_row) as usize][(col - min_col) as usize] = true;
+        }
+
+        Ok(Board::new(grid))
+    }
+
+    /// Copies the `rows` x `cols` area whose top left cell is (`row`, `col`).
+    pub fn region(&self, row: usize, col: usize, rows: usize, cols: usize) -> Board {
+        let grid = self.grid[row..row + rows]
+            .iter()
+            .map(|cells| cells[col..col + cols].to_vec())
+            .collect();
+        let age = match self.has_age() {
+            true => self.age[row..row + rows]
+                .iter()
+                .map(|ages| ages[col..col + cols].to_vec())
+                .collect(),
+            false => Vec::new(),
+        };
+
+        Board {
+            rule: self.rule.clone(),
+            boundary: self.boundary,
+            age,
+            ..Board::new(grid)
+        }
+    }
+
+    /// Shrinks the board to the bounding box of its live cells, keeping up to
+    /// `margin` cells around it, and returns the `(row, col)` of the kept
+    /// area. A board without live cells becomes a single dead cell.
+    pub fn crop_to_live(&mut self, margin: usize) -> (usize, usize) {
+        let (row, col, rows, cols) = match self.live_bounds() {
+            Some((row, col, rows, cols)) => {
+                let (top, left) = (row.saturating_sub(margin), col.saturating_sub(margin));
+                let bottom = (row + rows).saturating_add(margin).min(self.rows());
+                let right = (col + cols).saturating_add(margin).min(self.cols());
+                (top, left, bottom - top, right - left)
+            }
+            None => (0, 0, self.rows().min(1), self.cols().min(1)),
+        };
+
+        let region = self.region(row, col, rows, cols);
+        self.grid = region.grid;
+        self.age = region.age;
+        (row, col)
+    }
+
+    /// A copy of the board mirrored across `axis`.
+    pub fn mirror(&self, axis: Axis) -> Board {
+        self.transform(
+            |grid| mirror_grid(grid, axis),
+            |ages| mirror_grid(ages, axis),
+        )
+    }
+
+    /// A copy of the board rotated 90° clockwise, so its rows and columns
+    /// swap.
+    pub fn rotate90(&self) -> Board {
+        self.transform(rotate_grid, rotate_grid)
+    }
+
+    /// A copy of the board with the same rule and boundary, whose grid and
+    /// ages (if tracked) are rearranged by `grid` and `ages`.
+    fn transform(
+        &self,
+        grid: impl Fn(&[Vec<bool>]) -> Vec<Vec<bool>>,
+        ages: impl Fn(&[Vec<u32>]) -> Vec<Vec<u32>>,
+    ) -> Board {
+        Board {
+            rule: self.rule.clone(),
+            boundary: self.boundary,
+            age: match self.has_age() {
+                true => ages(&self.age),
+                false => Vec::new(),
+            },
+            ..Board::new(grid(&self.grid))
+        }
+    }
+
+    /// Bounding box of the live cells, as `(row, col, rows, cols)`, or `None`
+    /// if every cell is dead.
+    pub fn live_bounds(&self) -> Option<(usize, usize, usize, usize)> {
+        let empty = Board::new(vec![vec![false; self.cols()]; self.rows()]);
+        self.changed_bounds(&empty)
+    }
+
+    /// Bounding box of the cells that differ from `other`, as
+    /// `(row, col, rows, cols)`, or `None` if no cells differ.
+    pub fn changed_bounds(&self, other: &Board) -> Option<(usize, usize, usize, usize)> {
+        let mut bounds: Option<(usize, usize, usize, usize)> = None;
+
+        for (row, (cells, other_cells)) in self.grid.iter().zip(&other.grid).enumerate() {
+            for (col, (cell, other_cell)) in cells.iter().zip(other_cells).enumerate() {
+                if cell == other_cell {
+                    continue;
+                }
+                bounds = Some(match bounds {
+                    None => (row, col, row, col),
+                    Some((r0, c0, r1, c1)) => (r0.min(row), c0.min(col), r1.max(row), c1.max(col)),
+                });
+            }
+        }
+
+        bounds.map(|(r0, c0, r1, c1)| (r0, c0, r1 - r0 + 1, c1 - c0 + 1))
+    }
+
+    pub fn fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// The age of the cell at a position, 0 if it's dead. Live cells the
+    /// board hasn't tracked yet count as newborn.
+    pub fn age(&self, row: usize, col: usize) -> u32 {
+        match self.age.get(row).and_then(|ages| ages.get(col)) {
+            Some(age) if self.grid[row][col] => (*age).max(1),
+            _ => self.grid[row][col] as u32,
+        }
+    }
+
+    /// Whether `age` has an entry for every cell of the grid.
+    fn has_age(&self) -> bool {
+        self.age.len() == self.grid.len()
+            && self
+                .age
+                .iter()
+                .zip(&self.grid)
+                .all(|(ages, cells)| ages.len() == cells.len())
+    }
+
+    /// The ages of live cells, row by row, capped at 255.
+    fn pack_ages(&self) -> Vec<u8> {
+        let mut packed = Vec::new();
+        for (row, cells) in self.grid.iter().enumerate() {
+            for (col, _) in cells.iter().enumerate().filter(|(_, cell)| **cell) {
+                packed.push(self.age(row, col).min(u8::MAX as u32) as u8);
+            }
+        }
+        packed
+    }
+
+    /// Starts tracking ages if the board doesn't have them for its grid yet,
+    /// counting every live cell as newborn.
+    fn track_age(&mut self) {
+        if !self.has_age() {
+            self.age = self
+                .grid
+                .iter()
+                .map(|cells| cells.iter().map(|cell| *cell as u32).collect())
+                .collect();
+        }
+    }
+
+    /// Grows an expanding board by a dead row or column on every side a live
+    /// cell touches, so the next step has room to be born into. The board
+    /// stops growing at [`MAX_CELLS`] and is clipped like a dead boundary.
+    fn expand(&mut self) {
+        let Some(sides) = self.expansion() else {
+            return;
+        };
+
+        if self.has_age() {
+            pad(&mut self.age, 0, sides);
+        }
+        pad(&mut self.grid, false, sides);
+    }
+
+    /// Grows `grid` on the sides the next step will grow this board on, so
+    /// cells kept from before the step line up with the board after it.
+    pub(crate) fn grow_like_next<T: Clone>(&self, grid: &mut Vec<Vec<T>>, dead: T) {
+        if let Some(sides) = self.expansion() {
+            pad(grid, dead, sides);
+        }
+    }
+
+    /// The `(top, bottom, left, right)` sides the next step grows the board
+    /// on, if it grows at all.
+    fn expansion(&self) -> Option<(bool, bool, bool, bool)> {
+        if self.boundary != Boundary::Expanding || self.rows() == 0 || self.cols() == 0 {
+            return None;
+        }
+
+        let (rows, cols) = (self.rows(), self.cols());
+        let sides = (
+            self.grid[0].contains(&true),
+            self.grid[rows - 1].contains(&true),
+            self.grid.iter().any(|cells| cells[0]),
+            self.grid.iter().any(|cells| cells[cols - 1]),
+        );
+        let (top, bottom, left, right) = sides;
+        let grown_rows = rows + top as usize + bottom as usize;
+        let grown_cols = cols + left as usize + right as usize;
+        if (grown_rows, grown_cols) == (rows, cols) || grown_rows * grown_cols > MAX_CELLS {
+            return None;
+        }
+
+        Some(sides)
+    }
+
+    pub fn population(&self) -> usize {
+        self.grid.iter().flatten().filter(|cell| **cell).count()
+    }
+
+    /// The board's cells in a compact binary form: big-endian `u32` rows and
+    /// cols, then one bit per cell, row by row.
+    pub fn to_packed(&self) -> Vec<u8> {
+        pack(&self.grid)
+    }
+
+    /// Reads a board written by [`Board::to_packed`], with conway's rule and
+    /// a dead boundary.
+    pub fn from_packed(packed: &[u8]) -> Result<Self, BoardError> {
+        unpack(packed).map(Board::new)
+    }
+
+    /// How many cells differ from `other`, or `None` if the boards aren't the
+    /// same size.
+    pub fn hamming(&self, other: &Board) -> Option<usize> {
+        if self.rows() != other.rows() || self.cols() != other.cols() {
+            return None;
+        }
+
+        let differing = self
+            .grid
+            .iter()
+            .flatten()
+            .zip(other.grid.iter().flatten())
+            .filter(|(a, b)| a != b)
+            .count();
+        Some(differing)
+    }
+
+    /// Steps like `next`, returning which cells were born and which died
+    /// rather than just how many changed. This compares the whole board, so
+    /// `next` stays the cheaper way to step.
+    pub fn next_delta(&mut self) -> Delta {
+        // grow first so the cells of the step line up with the board before it
+        self.expand();
+        let before = self.grid.clone();
+        self.next();
+
+        let mut delta = Delta::default();
+        for (row, (cells, before)) in self.grid.iter().zip(&before).enumerate() {
+            for (col, (cell, was)) in cells.iter().zip(before).enumerate() {
+                match (was, cell) {
+                    (false, true) => delta.born.push((row, col)),
+                    (true, false) => delta.died.push((row, col)),
+                    _ => {}
+                }
+            }
+        }
+
+        delta
+    }
+
+    /// Steps like `next`, then flips each cell with probability `p`. The delta
+    /// counts every cell that differs from the previous generation.
+    pub fn next_noisy(&mut self, p: f64, rng: &mut impl Rng) -> i32 {
+        // grow first so the cells of the step line up with the board before it
+        self.expand();
+        let prev = self.grid.clone();
+        self.next();
+
+        let mut delta = 0;
+        for ((cells, ages), prev_cells) in self.grid.iter_mut().zip(&mut self.age).zip(&prev) {
+            for ((cell, age), prev_cell) in cells.iter_mut().zip(ages).zip(prev_cells) {
+                if rng.gen_bool(p) {
+                    *cell = !*cell;
+                    *age = *cell as u32;
+                }
+                if cell != prev_cell {
+                    delta += 1;
+                }
+            }
+        }
+
+        delta
+    }
+
+    /// Counts groups of live cells connected through any of their 8 neighbors.
+    pub fn clusters(&self) -> usize {
+        let mut seen = vec![vec![false; self.cols()]; self.rows()];
+        let mut count = 0;
+
+        for (row, cells) in self.grid.iter().enumerate() {
+            for (col, alive) in cells.iter().enumerate() {
+                if !alive || seen[row][col] {
+                    continue;
+                }
+
+                count += 1;
+                seen[row][col] = true;
+                let mut stack = vec![(row as isize, col as isize)];
+                while let Some((r, c)) = stack.pop() {
+                    for (dr, dc) in NEIGHBORS {
+                        let (nr, nc) = (r + dr, c + dc);
+                        if self.get(nr, nc) && !seen[nr as usize][nc as usize] {
+                            seen[nr as usize][nc as usize] = true;
+                            stack.push((nr, nc));
+                        }
+                    }
+                }
+            }
+        }
+
+        count
+    }
+
+    /// Updates cells one at a time in place, so later cells see the new state
+    /// of earlier ones, unlike the synchronous `next`.
+    pub fn next_async(&mut self, order: UpdateOrder, rng: &mut impl Rng) -> i32 {
+        self.expand();
+        let mut cells = (0..self.rows())
+            .flat_map(|row| (0..self.cols()).map(move |col| (row, col)))
+            .collect::<Vec<_>>();
+        if order == UpdateOrder::Random {
+            cells.shuffle(rng);
+        }
+        self.track_age();
+
+        let mut delta = 0;
+        for (row, col) in cells {
+            let (next_state, has_changed) = self.interact(row, col);
+            if has_changed {
+                delta += 1;
+            }
+            self.grid[row][col] = next_state;
+            let age = &mut self.age[row][col];
+            *age = if next_state { age.saturating_add(1) } else { 0 };
+        }
+
+        delta
+    }
+
+    pub fn rows(&self) -> usize {
+        self.grid.len()
+    }
+
+    pub fn cols(&self) -> usize {
+        self.grid.first().map_or(0, Vec::len)
+    }
+
+    /// Steps the live cells as a [`SparseBoard`] and writes back the cells
+    /// that changed.
+    fn next_sparse(&mut self) -> i32 {
+        let before = SparseBoard::from(&*self);
+        let mut after = before.clone();
+        let delta = after.next();
+        for (row, col) in before.cells.symmetric_difference(&after.cells) {
+            self.grid[*row as usize][*col as usize] ^= true;
+        }
+        for (row, col) in before.cells.difference(&after.cells) {
+            self.age[*row as usize][*col as usize] = 0;
+        }
+        for (row, col) in &after.cells {
+            let age = &mut self.age[*row as usize][*col as usize];
+            *age = age.saturating_add(1);
+        }
+        delta
+    }
+
+    /// Steps every cell into the scratch buffer, rows across threads when
+    /// `parallel` and the rayon feature is on.
+    #[cfg_attr(not(feature = "rayon"), allow(unused_variables))]
+    fn next_dense(&mut self, parallel: bool) -> i32 {
+        let mut next = std::mem::take(&mut self.scratch.0);
+        next.resize(self.grid.len(), Vec::new());
+        for (cells, row) in next.iter_mut().zip(&self.grid) {
+            cells.resize(row.len(), false);
+        }
+        // rows are stepped independently, so they can be in parallel
+        let mut ages = std::mem::take(&mut self.age);
+        let step_row = |(row, (cells, ages)): (usize, (&mut Vec<bool>, &mut Vec<u32>))| {
+            let mut delta = 0;
+            for (col, (cell, age)) in cells.iter_mut().zip(ages.iter_mut()).enumerate() {
+                let (next_state, has_changed) = self.interact(row, col);
+                if has_changed {
+                    delta += 1;
+                }
+                *cell = next_state;
+                *age = if next_state { age.saturating_add(1) } else { 0 };
+            }
+            delta
+        };
+
+        #[cfg(feature = "rayon")]
+        let delta = if parallel {
+            use rayon::prelude::*;
+            next.par_iter_mut()
+                .zip(ages.par_iter_mut())
+                .enumerate()
+                .map(step_row)
+                .sum()
+        } else {
+            next.iter_mut()
+                .zip(ages.iter_mut())
+                .enumerate()
+                .map(step_row)
+                .sum()
+        };
+        #[cfg(not(feature = "rayon"))]
+        let delta = next
+            .iter_mut()
+            .zip(ages.iter_mut())
+            .enumerate()
+            .map(step_row)
+            .sum();
+
+        self.age = ages;
+        std::mem::swap(&mut self.grid, &mut next);
+        self.scratch.0 = next;
+        delta
+    }
+
+    /// Steps a generation, returning how many cells changed. Same as
+    /// [`Evolve::next`], without needing the trait in scope.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> i32 {
+        Evolve::next(self)
+    }
+
+    /// Same as [`Evolve::stringify`], without needing the trait in scope.
+    pub fn stringify(
+        &self,
+        alive: Option<char>,
+        dead: Option<char>,
+        separator: Option<char>,
+    ) -> String {
+        Evolve::stringify(self, alive, dead, separator)
+    }
+}
+
+fn mirror_grid<T: Clone>(grid: &[Vec<T>], axis: Axis) -> Vec<Vec<T>> {
+    let mut mirrored = grid.to_vec();
+    if matches!(axis, Axis::Vertical | Axis::Both) {
+        mirrored.reverse();
+    }
+    if matches!(axis, Axis::Horizontal | Axis::Both) {
+        mirrored.iter_mut().for_each(|cells| cells.reverse());
+    }
+
+    mirrored
+}
+
+/// Rotates a grid 90° clockwise: the bottom row becomes the first column.
+fn rotate_grid<T: Clone>(grid: &[Vec<T>]) -> Vec<Vec<T>> {
+    let cols = grid.first().map_or(0, Vec::len);
+    (0..cols)
+        .map(|col| grid.iter().rev().map(|cells| cells[col].clone()).collect())
+        .collect()
+}
+
+/// Adds a row or column of `dead` to each `(top, bottom, left, right)` side
+/// that is set.
+fn pad<T: Clone>(
+    grid: &mut Vec<Vec<T>>,
+    dead: T,
+    (top, bottom, left, right): (bool, bool, bool, bool),
+) {
+    for cells in grid.iter_mut() {
+        if left {
+            cells.insert(0, dead.clone());
+        }
+        if right {
+            cells.push(dead.clone());
+        }
+    }
+
+    let cols = grid.first().map_or(0, Vec::len);
+    if top {
+        grid.insert(0, vec![dead.clone(); cols]);
+    }
+    if bottom {
+        grid.push(vec![dead; cols]);
+    }
+}
+
+/// Stepping shared by the dense [`Board`] and the [`SparseBoard`], which only
+/// differ in how they store and visit cells.
+pub trait Evolve {
+    fn rows(&self) -> usize;
+    fn cols(&self) -> usize;
+    fn rule(&self) -> &Rule;
+    fn boundary(&self) -> Boundary;
+
+    /// Whether the cell at a position on the board is alive.
+    fn is_alive(&self, row: usize, col: usize) -> bool;
+
+    /// Steps a generation, returning how many cells changed.
+    fn next(&mut self) -> i32;
+
+    /// The position on the board a cell refers to. Cells off the board wrap
+    /// around on a torus and are `None` otherwise.
+    fn wrap(&self, row: isize, col: isize) -> Option<(usize, usize)> {
+        let (row, col) = match self.boundary() {
+            Boundary::Toroidal => (
+                row.rem_euclid(self.rows().max(1) as isize),
+                col.rem_euclid(self.cols().max(1) as isize),
+            ),
+            Boundary::Dead | Boundary::Expanding => (row, col),
+        };
+
+        let on_board =
+            (0..self.rows() as isize).contains(&row) && (0..self.cols() as isize).contains(&col);
+        on_board.then_some((row as usize, col as usize))
+    }
+
+    fn get(&self, row: isize, col: isize) -> bool {
+        self.wrap(row, col)
+            .is_some_and(|(row, col)| self.is_alive(row, col))
+    }
+
+    fn neighbors(&self, row: usize, col: usize) -> usize {
+        NEIGHBORS
+            .iter()
+            .filter(|(r, c)| self.get(row as isize + r, col as isize + c))
+            .count()
+    }
+
+    /// The next state of a cell and whether it changed.
+    fn interact(&self, row: usize, col: usize) -> (bool, bool) {
+        let neighbors = self.neighbors(row, col);
+        let alive = self.is_alive(row, col);
+
+        // live cells survive and dead cells are born on the rule's counts
+        let next = match alive {
+            true => self.rule().survival.contains(&(neighbors as u8)),
+            false => self.rule().birth.contains(&(neighbors as u8)),
+        };
+
+        (next, next != alive)
+    }
+
+    fn stringify(
+        &self,
+        alive: Option<char>,
+        dead: Option<char>,
+        separator: Option<char>,
+    ) -> String {
+        let alive = alive.unwrap_or(ALIVE);
+        let dead = dead.unwrap_or(DEAD);
+        let separator = separator.unwrap_or(SEPARATOR);
+
+        let mut result = String::with_capacity(self.rows() * self.cols() + self.rows());
+
+        for row in 0..self.rows() {
+            for col in 0..self.cols() {
+                result.push(if self.is_alive(row, col) { alive } else { dead });
+            }
+            if row + 1 < self.rows() {
+                result.push(separator);
+            }
+        }
+
+        result
+    }
+}
+
+impl Evolve for Board {
+    fn rows(&self) -> usize {
+        Board::rows(self)
+    }
+
+    fn cols(&self) -> usize {
+        Board::cols(self)
+    }
+
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    fn is_alive(&self, row: usize, col: usize) -> bool {
+        self.grid[row].get(col).is_some_and(|cell| *cell)
+    }
+
+    /// Steps every cell, unless few enough are alive that stepping them as a
+    /// [`SparseBoard`] and writing back the changes is cheaper.
+    fn next(&mut self) -> i32 {
+        self.expand();
+        self.track_age();
+        let sparse = !self.rule.birth.contains(&0)
+            && (self.population() as f64) < SPARSE_DENSITY * (self.rows() * self.cols()) as f64;
+        if sparse {
+            return self.next_sparse();
+        }
+
+        #[cfg(feature = "rayon")]
+        let parallel = self.rows() * self.cols() >= PARALLEL_CELLS;
+        #[cfg(not(feature = "rayon"))]
+        let parallel = false;
+        self.next_dense(parallel)
+    }
+}
+
+/// A board kept as the `(row, col)` of its live cells, for large boards with
+/// few of them. Steps only visit the live cells and their neighbors.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SparseBoard {
+    pub cells: HashSet<(i64, i64)>,
+    pub rows: usize,
+    pub cols: usize,
+    pub rule: Rule,
+    pub boundary: Boundary,
+}
+
+impl From<&Board> for SparseBoard {
+    fn from(board: &Board) -> Self {
+        let cells = board
+            .grid
+            .iter()
+            .enumerate()
+            .flat_map(|(row, cells)| {
+                cells
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, alive)| **alive)
+                    .map(move |(col, _)| (row as i64, col as i64))
+            })
+            .collect();
+
+        SparseBoard {
+            cells,
+            rows: board.rows(),
+            cols: board.cols(),
+            rule: board.rule.clone(),
+            boundary: board.boundary,
+        }
+    }
+}
+
+impl From<&SparseBoard> for Board {
+    fn from(sparse: &SparseBoard) -> Self {
+        let mut grid = vec![vec![false; sparse.cols]; sparse.rows];
+        for (row, col) in &sparse.cells {
+            grid[*row as usize][*col as usize] = true;
+        }
+
+        Board {
+            rule: sparse.rule.clone(),
+            boundary: sparse.boundary,
+            ..Board::new(grid)
+        }
+    }
+}
+
+impl Evolve for SparseBoard {
+    fn rows(&self) -> usize {
+        self.rows
+    }
+
+    fn cols(&self) -> usize {
+        self.cols
+    }
+
+    fn rule(&self) -> &Rule {
+        &self.rule
+    }
+
+    fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    fn is_alive(&self, row: usize, col: usize) -> bool {
+        self.cells.contains(&(row as i64, col as i64))
+    }
+
+    fn next(&mut self) -> i32 {
+        // with B0 isolated dead cells are born too, so every cell is a candidate
+        let candidates: HashSet<(usize, usize)> = if self.rule.birth.contains(&0) {
+            (0..self.rows)
+                .flat_map(|row| (0..self.cols).map(move |col| (row, col)))
+                .collect()
+        } else {
+            self.cells
+                .iter()
+                .flat_map(|(row, col)| {
+                    std::iter::once((0, 0))
+                        .chain(NEIGHBORS)
+                        .map(move |(r, c)| (*row as isize + r, *col as isize + c))
+                })
+                .filter_map(|(row, col)| self.wrap(row, col))
+                .collect()
+        };
+
+        let mut next = HashSet::with_capacity(self.cells.len());
+        let mut delta = 0;
+        for (row, col) in candidates {
+            let (next_state, has_changed) = self.interact(row, col);
+            if has_changed {
+                delta += 1;
+            }
+            if next_state {
+                next.insert((row as i64, col as i64));
+            }
+        }
+
+        self.cells = next;
+        delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seed(seed: &str, opts: SeedOptions) -> Vec<Vec<bool>> {
+        Board::from_seed(seed.to_string(), opts).unwrap().grid
+    }
+
+    #[test]
+    fn trimming_keeps_leading_dead_spaces() {
+        let lenient = SeedOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            seed("  #\n###", lenient),
+            vec![vec![false, false, true], vec![true, true, true]]
+        );
+
+        let spaces = SeedOptions::new(None, Some(' '), None, None, None);
+        assert_eq!(
+            seed("\n  #\n###\n\n", spaces),
+            vec![vec![false, false, true], vec![true, true, true]]
+        );
+    }
+
+    #[test]
+    fn trimming_drops_blank_border_rows() {
+        let grid = seed("\r\n  \n#.\r\n.#\r\n\n", SeedOptions::default());
+        assert_eq!(grid, vec![vec![true, false], vec![false, true]]);
+    }
+
+    #[test]
+    fn detects_separators_of_space_padded_seeds() {
+        let mut opts = SeedOptions {
+            lenient: true,
+            ..Default::default()
+        };
+        opts.detect_separator("  #|###\n");
+        assert_eq!(opts.separator, '|');
+    }
+
+    #[test]
+    fn rle_rejects_runs_past_the_board() {
+        for input in [
+            "x = 3, y = 1\no18446744073709551615o!",
+            "x = 3, y = 1\n4o!",
+            "x = 3, y = 2\no18446744073709551615$o!",
+            "x = 3, y = 1\no2$o!",
+        ] {
+            assert!(Board::from_rle(input).is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn centered_rejects_oversized_empty_sides() {
+        for (rows, cols) in [(0, usize::MAX), (usize::MAX, 0), (2_000_000, 0)] {
+            assert!(matches!(
+                Board::from_centered_coords(&[], rows, cols),
+                Err(BoardError::TooLarge { .. })
+            ));
+        }
+        assert!(Board::from_centered_coords(&[(0, 0)], 0, 0).is_err());
+    }
+
+    #[test]
+    fn rle_rejects_oversized_empty_sides() {
+        for input in [
+            "x = 18446744073709551615, y = 0\n!",
+            "x = 0, y = 18446744073709551615\n!",
+        ] {
+            assert!(matches!(
+                Board::from_rle(input),
+                Err(BoardError::TooLarge { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn ages_survive_storing() {
+        let mut board = Board::new(vec![vec![false, true, false]; 3]);
+        for _ in 0..300 {
+            board.next();
+        }
+        let stored: Board = serde_json::from_str(&serde_json::to_string(&board).unwrap()).unwrap();
+        assert_eq!(stored.grid, board.grid);
+        for (row, col) in [(1, 0), (1, 1), (1, 2)] {
+            assert_eq!(stored.age(row, col), board.age(row, col).min(255));
+        }
+        assert_eq!(stored.age(1, 1), 255);
+    }
+
+    #[test]
+    fn stored_ages_stay_small() {
+        let mut grid = vec![vec![false; 1000]; 1000];
+        grid[500][499..502].fill(true);
+        let mut board = Board::new(grid);
+        let before = serde_json::to_string(&board).unwrap().len();
+        board.next();
+        let after = serde_json::to_string(&board).unwrap().len();
+        assert!(after < before + 16, "{before} grew to {after}");
+    }
+
+    #[test]
+    fn reads_ages_stored_for_every_cell() {
+        let json = r#"{"grid":[[true,false]],"age":[[7,0]]}"#;
+        let board: Board = serde_json::from_str(json).unwrap();
+        assert_eq!(board.age(0, 0), 7);
+    }
+
+    fn board(seed: &str) -> Board {
+        Board::from_seed(seed.to_string(), SeedOptions::default()).unwrap()
+    }
+
+    #[test]
+    fn detects_separators() {
+        for (input, separator) in [
+            ("#.\n.#", '\n'),
+            ("#.\r\n.#\r\n", '\n'),
+            ("#.|.#|##", '|'),
+            ("#.,.#", ','),
+            ("#.#", '\n'),
+        ] {
+            let mut opts = SeedOptions::default();
+            opts.detect_separator(input);
+            assert_eq!(opts.separator, separator, "{input:?}");
+        }
+
+        let mut opts = SeedOptions::default();
+        opts.detect_separator("#.\r\n.#\r\n");
+        assert_eq!(seed("#.\r\n.#\r\n", opts), board("#.\n.#").grid);
+    }
+
+    #[test]
+    fn highlife_births_on_six_neighbors() {
+        let seed = "###.\n#.#.\n#...\n....";
+        let mut life = board(seed);
+        let mut highlife = board(seed);
+        highlife.rule = Rule::parse("B36/S23").unwrap();
+
+        life.next();
+        highlife.next();
+        assert!(!life.grid[1][1]);
+        assert!(highlife.grid[1][1]);
+    }
+
+    #[test]
+    fn highlife_replicator_differs_from_life() {
+        let mut grid = vec![vec![false; 32]; 32];
+        for (row, cells) in ["..###", ".#..#", "#...#", "#..#.", "###.."]
+            .iter()
+            .enumerate()
+        {
+            for (col, cell) in cells.chars().enumerate() {
+                grid[row + 13][col + 13] = cell == '#';
+            }
+        }
+        let mut life = Board::new(grid);
+        let mut highlife = Board {
+            rule: Rule::parse("B36/S23").unwrap(),
+            ..life.clone()
+        };
+
+        for _ in 0..12 {
+            life.next();
+            highlife.next();
+        }
+        assert_ne!(life.grid, highlife.grid);
+    }
+
+    #[test]
+    fn parses_rules() {
+        let seeds = Rule::parse("b2/s").unwrap();
+        assert_eq!(seeds.birth, HashSet::from([2]));
+        assert!(seeds.survival.is_empty());
+        assert_eq!(Rule::parse("B36/S23").unwrap().to_string(), "B36/S23");
+        assert_eq!(Rule::default().to_string(), "B3/S23");
+        assert!(matches!(
+            Rule::parse("B3S23"),
+            Err(RuleError::InvalidFormat(_))
+        ));
+        assert_eq!(Rule::parse("B9/S23"), Err(RuleError::InvalidCount('9')));
+    }
+
+    #[test]
+    fn glider_wraps_around_a_torus() {
+        let mut glider =
+            board(".#......\n..#.....\n###.....\n........\n........\n........\n........\n........");
+        glider.boundary = Boundary::Toroidal;
+        let start = glider.grid.clone();
+
+        for generation in 1..=4 * glider.cols() {
+            glider.next();
+            assert_eq!(glider.population(), 5);
+            assert_eq!(glider.grid == start, generation == 4 * glider.cols());
+        }
+    }
+
+    #[test]
+    fn glider_dies_on_a_dead_boundary() {
+        let mut glider = board(".#...\n..#..\n###..\n.....\n.....");
+        for _ in 0..20 {
+            glider.next();
+        }
+        assert_ne!(glider.population(), 5);
+    }
+
+    #[test]
+    fn rejects_empty_seeds() {
+        for seed in ["", " ", "\n\n", " \t\r\n "] {
+            let err = Board::from_seed(seed.to_string(), SeedOptions::default());
+            assert_eq!(err.err(), Some(BoardError::EmptySeed));
+        }
+        assert_eq!(BoardError::EmptySeed.status_code(), StatusCode::BAD_REQUEST);
+    }
+
+    #[test]
+    fn empty_boards_have_no_columns() {
+        let mut empty = Board::new(Vec::new());
+        assert_eq!((empty.rows(), empty.cols()), (0, 0));
+        assert_eq!(empty.stringify(None, None, None), "");
+        assert_eq!(empty.next(), 0);
+    }
+
+    #[test]
+    fn reads_rle_glider() {
+        let glider =
+            Board::from_rle("#N Glider\nx = 3, y = 3, rule = B3/S23\nbob$2bo$3o!").unwrap();
+        assert_eq!(glider.grid, board(".#.\n..#\n###").grid);
+        assert_eq!(glider.rule, Rule::default());
+    }
+
+    #[test]
+    fn reads_rle_pulsar() {
+        let pulsar = Board::from_rle(
+            "x = 13, y = 13, rule = B3/S23\n\
+             2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$\n\
+             2b3o3b3o2b$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+        )
+        .unwrap();
+        assert_eq!((pulsar.rows(), pulsar.cols()), (13, 13));
+        assert_eq!(pulsar.population(), 48);
+
+        // a period 3 oscillator, with room to grow into between phases
+        let mut grid = vec![vec![false; 17]; 17];
+        for (row, cells) in pulsar.grid.iter().enumerate() {
+            grid[row + 2][2..15].copy_from_slice(cells);
+        }
+        let mut pulsar = Board::new(grid);
+        let start = pulsar.grid.clone();
+        pulsar.next();
+        assert_ne!(pulsar.grid, start);
+        pulsar.next();
+        pulsar.next();
+        assert_eq!(pulsar.grid, start);
+    }
+
+    #[test]
+    fn rle_rejects_bad_headers_and_tags() {
+        for input in [
+            "",
+            "y = 3\nbo!",
+            "x = a, y = 3\nbo!",
+            "x = 3, y = 3, z = 1\n!",
+        ] {
+            assert!(matches!(
+                Board::from_rle(input),
+                Err(BoardError::InvalidRleHeader(_))
+            ));
+        }
+        assert_eq!(
+            Board::from_rle("x = 3, y = 1\nbqo!").err(),
+            Some(BoardError::UnexpectedRleTag('q'))
+        );
+    }
+
+    fn random(rows: usize, cols: usize, density: f64, seed: u64) -> Board {
+        use rand::{rngs::StdRng, SeedableRng};
+        Board::random(rows, cols, density, &mut StdRng::seed_from_u64(seed)).unwrap()
+    }
+
+    /// Steps every cell of the board, however few are alive.
+    fn next_dense(board: &mut Board, parallel: bool) -> i32 {
+        board.expand();
+        board.track_age();
+        board.next_dense(parallel)
+    }
+
+    #[test]
+    fn dense_and_sparse_steps_match() {
+        for boundary in [Boundary::Dead, Boundary::Toroidal, Boundary::Expanding] {
+            let mut dense = random(64, 64, 0.01, 7);
+            dense.boundary = boundary;
+            let mut sparse = dense.clone();
+            sparse.track_age();
+
+            for _ in 0..100 {
+                let delta = next_dense(&mut dense, false);
+                sparse.expand();
+                assert_eq!(sparse.next_sparse(), delta);
+                assert_eq!(sparse.grid, dense.grid);
+                assert_eq!(sparse.age, dense.age);
+            }
+        }
+    }
+
+    #[test]
+    fn lone_glider_matches_sparse_board() {
+        let mut grid = vec![vec![false; 40]; 40];
+        grid[0][1] = true;
+        grid[1][2] = true;
+        grid[2][..3].fill(true);
+        let mut board = Board {
+            boundary: Boundary::Toroidal,
+            ..Board::new(grid)
+        };
+        let mut sparse = SparseBoard::from(&board);
+
+        for _ in 0..100 {
+            assert_eq!(board.next(), sparse.next());
+            assert_eq!(Board::from(&sparse).grid, board.grid);
+        }
+        assert_eq!(board.population(), 5);
+    }
+
+    #[test]
+    fn reused_buffers_step_like_fresh_boards() {
+        let mut reused = random(32, 32, 0.3, 11);
+        for _ in 0..200 {
+            let mut fresh = Board::new(reused.grid.clone());
+            assert_eq!(
+                next_dense(&mut reused, false),
+                next_dense(&mut fresh, false)
+            );
+            assert_eq!(reused.grid, fresh.grid);
+        }
+    }
+
+    #[test]
+    fn strict_dimensions_reject_ragged_rows() {
+        let strict = SeedOptions {
+            strict_dimensions: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            Board::from_seed("##\n#\n##".to_string(), strict).err(),
+            Some(BoardError::RaggedRows {
+                expected: 2,
+                found: 1,
+                row: 1,
+            })
+        );
+        assert_eq!(
+            seed("##\n#\n##", SeedOptions::default()),
+            vec![vec![true, true], vec![true, false], vec![true, true]]
+        );
+    }
+
+    #[test]
+    fn block_ages_while_blinker_resets() {
+        let mut block = board("....\n.##.\n.##.\n....");
+        let mut blinker = board(".....\n.....\n.###.\n.....\n.....");
+        for generation in 1..=6 {
+            block.next();
+            blinker.next();
+            assert_eq!(block.age(1, 1), generation + 1);
+            assert_eq!(block.age(0, 0), 0);
+            assert_eq!(blinker.age(2, 2), generation + 1);
+            // the arms are born again every other generation
+            let arms = match generation % 2 {
+                1 => [(1, 2), (3, 2)],
+                _ => [(2, 1), (2, 3)],
+            };
+            for (row, col) in arms {
+                assert_eq!(blinker.age(row, col), 1);
+            }
+        }
+    }
+
+    #[test]
+    fn rotates_glider() {
+        let glider = board(".#.\n..#\n###");
+        assert_eq!(glider.rotate90().grid, board("#..\n#.#\n##.").grid);
+
+        let wide = board("##.\n...");
+        let rotated = wide.rotate90();
+        assert_eq!((rotated.rows(), rotated.cols()), (3, 2));
+        assert_eq!(rotated.grid, board(".#\n.#\n..").grid);
+        let full_turn = (0..4).fold(wide.clone(), |board, _| board.rotate90());
+        assert_eq!(full_turn.grid, wide.grid);
+    }
+
+    #[test]
+    fn mirroring_twice_is_identity() {
+        let glider = board(".#.\n..#\n###");
+        assert_eq!(
+            glider.mirror(Axis::Horizontal).grid,
+            board(".#.\n#..\n###").grid
+        );
+        for axis in [Axis::Horizontal, Axis::Vertical, Axis::Both] {
+            assert_eq!(glider.mirror(axis).mirror(axis).grid, glider.grid);
+        }
+    }
+
+    #[cfg(feature = "rayon")]
+    #[test]
+    fn parallel_steps_match_sequential() {
+        let mut parallel = random(128, 128, 0.3, 13);
+        let mut sequential = parallel.clone();
+        for _ in 0..50 {
+            assert_eq!(
+                next_dense(&mut parallel, true),
+                next_dense(&mut sequential, false)
+            );
+            assert_eq!(parallel.grid, sequential.grid);
+            assert_eq!(parallel.age, sequential.age);
+        }
+    }
+
+    #[test]
+    fn reports_position_of_bad_seed_characters() {
+        let err = Board::from_seed("#.#\n.#.\n#x#".to_string(), SeedOptions::default());
+        assert_eq!(
+            err.err(),
+            Some(BoardError::InvalidSeedCharacter {
+                found: 'x',
+                alive: '#',
+                dead: '.',
+                row: 2,
+                col: 1,
+            })
+        );
+        let message = Board::from_seed(".#\n.?".to_string(), SeedOptions::default())
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(message.contains("at row 1, col 1"), "{message}");
+    }
+
+    #[test]
+    fn hamming_counts_differing_cells() {
+        let glider = board(".#.\n..#\n###");
+        assert_eq!(glider.hamming(&glider), Some(0));
+        assert_eq!(glider.hamming(&board("...\n..#\n###")), Some(1));
+        assert_eq!(glider.hamming(&board("##\n##")), None);
+    }
+
+    #[test]
+    fn rejects_truncated_packed_boards() {
+        let packed = board("##\n##").to_packed();
+        assert!(matches!(
+            Board::from_packed(&packed[..packed.len() - 1]),
+            Err(BoardError::InvalidPacked(_))
+        ));
+    }
+
+    #[test]
+    fn reads_boards_stored_as_bools() {
+        let stored: Board = serde_json::from_str(r#"{"grid":[[true,false],[false]]}"#).unwrap();
+        assert_eq!(stored.grid, vec![vec![true, false], vec![false, false]]);
+        assert_eq!(stored.rule, Rule::default());
+        assert_eq!(stored.boundary, Boundary::Dead);
+    }
+
+    #[test]
+    fn packing_keeps_dead_borders() {
+        let mut grid = vec![vec![false; 5]; 5];
+        grid[0][0] = true;
+        grid[1][2] = true;
+        let board = Board::new(grid);
+
+        let unpacked = Board::from_packed(&board.to_packed()).unwrap();
+        assert_eq!((unpacked.rows(), unpacked.cols()), (5, 5));
+        assert_eq!(unpacked.grid, board.grid);
+
+        let stored: Board = serde_json::from_str(&serde_json::to_string(&board).unwrap()).unwrap();
+        assert_eq!(stored.grid, board.grid);
+    }
+
+    fn game(seed: &str) -> Game {
+        Game::from(board(seed))
+    }
+
+    #[test]
+    fn classifies_still_lifes_and_oscillators() {
+        assert_eq!(
+            game("....\n.##.\n.##.\n....").classify(10),
+            Classification::StillLife
+        );
+        assert_eq!(
+            game(".....\n..#..\n..#..\n..#..\n.....").classify(10),
+            Classification::Oscillator { period: 2 }
+        );
+    }
+
+    #[test]
+    fn classifies_gliders_as_spaceships() {
+        let mut glider = game(".#.\n..#\n###");
+        glider.board.boundary = Boundary::Expanding;
+        assert_eq!(
+            glider.classify(10),
+            Classification::Spaceship {
+                period: 4,
+                dx: 1,
+                dy: 1
+            }
+        );
+    }
+
+    #[test]
+    fn r_pentomino_does_not_settle_within_the_bound() {
+        let mut r_pentomino = game(".##\n##.\n.#.");
+        r_pentomino.board.boundary = Boundary::Expanding;
+        assert_eq!(r_pentomino.classify(100), Classification::Growing);
+    }
+
+    /// A pulsar with room around it for its larger phases.
+    fn pulsar() -> Game {
+        let pulsar = Board::from_rle(
+            "x = 13, y = 13\n\
+             2b3o3b3o2b2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2b2$\n\
+             2b3o3b3o2b$o4bobo4bo$o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+        )
+        .unwrap();
+        let mut grid = vec![vec![false; 17]; 17];
+        for (row, cells) in pulsar.grid.iter().enumerate() {
+            grid[row + 2][2..15].copy_from_slice(cells);
+        }
+        Game::from(Board::new(grid))
+    }
+
+    #[test]
+    fn classifies_oscillator_periods() {
+        let mut blinker = game(".....\n..#..\n..#..\n..#..\n.....");
+        assert_eq!(
+            blinker.classify(10),
+            Classification::Oscillator { period: 2 }
+        );
+        assert_eq!(
+            pulsar().classify(10),
+            Classification::Oscillator { period: 3 }
+        );
+    }
+
+    #[test]
+    fn detects_periods() {
+        let blinker = game(".....\n..#..\n..#..\n..#..\n.....");
+        assert_eq!(blinker.detect_period(10), Some(2));
+        assert_eq!(blinker.generation, 0);
+        assert_eq!(pulsar().detect_period(10), Some(3));
+        assert_eq!(pulsar().detect_period(2), None);
+        assert_eq!(game("##\n##").detect_period(10), Some(1));
+    }
+
+    #[test]
+    fn step_n_runs_every_step_of_a_glider() {
+        let mut glider =
+            game(".#......\n..#.....\n###.....\n........\n........\n........\n........\n........");
+        glider.board.boundary = Boundary::Toroidal;
+        let report = glider.step_n(20);
+        assert_eq!(report.generations, 20);
+        assert_eq!(report.total_delta, 20 * 4);
+        assert!(!report.became_terminal);
+        assert_eq!(glider.generation, 20);
+
+        let report = glider.run_until_terminal(20);
+        assert_eq!(report.generations, 20);
+        assert!(!report.became_terminal);
+    }
+
+    #[test]
+    fn run_until_terminal_stops_early_at_a_block() {
+        let mut pre_block = game("##.\n#..\n...");
+        let report = pre_block.run_until_terminal(100);
+        assert_eq!(
+            report,
+            StepReport {
+                generations: 2,
+                total_delta: 1,
+                became_terminal: true
+            }
+        );
+        assert_eq!(pre_block.board.grid, board("##.\n##.\n...").grid);
+        assert_eq!(pre_block.run_until_terminal(100).generations, 0);
+    }
+
+    #[test]
+    fn blinker_delta_has_two_born_and_two_died() {
+        let mut blinker = board(".....\n..#..\n..#..\n..#..\n.....");
+        let delta = blinker.next_delta();
+        assert_eq!(delta.born, vec![(2, 1), (2, 3)]);
+        assert_eq!(delta.died, vec![(1, 2), (3, 2)]);
+        assert_eq!(delta.len(), 4);
+
+        let delta = blinker.next_delta();
+        assert_eq!(delta.born, vec![(1, 2), (3, 2)]);
+        assert_eq!(delta.died, vec![(2, 1), (2, 3)]);
+    }
+
+    #[test]
+    fn expires_at_its_time() {
+        let mut game = game("#");
+        assert!(!game.is_expired(u64::MAX));
+        game.expires_at = Some(100);
+        assert!(!game.is_expired(99));
+        assert!(game.is_expired(100));
+        assert!(game.is_expired(101));
+    }
+
+    #[test]
+    fn unpack_rejects_oversized_empty_sides() {
+        for (rows, cols) in [(u32::MAX, 0), (0, u32::MAX), (2_000_000, 0)] {
+            let mut packed = rows.to_be_bytes().to_vec();
+            packed.extend_from_slice(&cols.to_be_bytes());
+            assert!(matches!(
+                Board::from_packed(&packed),
+                Err(BoardError::TooLarge { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn life106_moves_negative_offsets_to_the_origin() {
+        let glider =
+            Board::from_life106("#Life 1.06\n#N glider\n0 -1\n1 0\n-1 1\n0 1\n1 1\n").unwrap();
+        assert_eq!(glider.grid, board(".#.\n..#\n###").grid);
+
+        assert!(matches!(
+            Board::from_life106("0 -1\n1 0"),
+            Err(BoardError::InvalidLife106(_))
+        ));
+        assert!(matches!(
+            Board::from_life106("#Life 1.06\n0 x"),
+            Err(BoardError::InvalidLife106(_))
+        ));
+    }
+
+    #[test]
+    fn crops_to_a_corner_and_to_nothing() {
+        let mut corner = board("....\n....\n...#");
+        assert_eq!(corner.crop_to_live(0), (2, 3));
+        assert_eq!(corner.grid, vec![vec![true]]);
+
+        let mut corner = board("....\n....\n...#");
+        assert_eq!(corner.crop_to_live(2), (0, 1));
+        assert_eq!(corner.stringify(None, None, None), "...\n...\n..#");
+
+        let mut dead = board("...\n...");
+        assert_eq!(dead.crop_to_live(1), (0, 0));
+        assert_eq!(dead.grid, vec![vec![false]]);
+    }
+
+    #[test]
+    fn parses_plaintext_cells() {
+        let glider = Board::from_cells("!Name: Glider\n!\n.O\n..O\nOOO\n").unwrap();
+        assert_eq!(glider.grid, board(".#.\n..#\n###").grid);
+
+        assert!(matches!(
+            Board::from_cells("!Name: Nothing\n!Just comments\n"),
+            Err(BoardError::EmptySeed)
+        ));
+    }
+
+    #[test]
+    fn expanding_boards_grow_toward_a_glider() {
+        let mut glider = board(".....\n..#..\n...#.\n.###.\n.....");
+        glider.boundary = Boundary::Expanding;
+        glider.next();
+        assert_eq!((glider.rows(), glider.cols()), (5, 5));
+
+        for _ in 1..8 {
+            glider.next();
+        }
+        // a glider travels one cell diagonally every four generations, the
+        // board grows toward it and never away from it
+        assert_eq!((glider.rows(), glider.cols()), (7, 7));
+        assert_eq!(glider.population(), 5);
+        assert!(!glider.grid[0].contains(&true));
+        assert!(glider.grid.iter().all(|cells| !cells[0]));
+
+        let mut clipped = glider.clone();
+        clipped.boundary = Boundary::Dead;
+        for _ in 0..8 {
+            clipped.next();
+            glider.next();
+        }
+        assert_eq!((clipped.rows(), clipped.cols()), (7, 7));
+        assert_ne!(clipped.population(), 5);
+        assert_eq!(glider.population(), 5);
+    }
+
+    #[test]
+    fn population_series_of_a_blinker_and_a_block() {
+        let mut blinker = game(".....\n..#..\n..#..\n..#..\n.....");
+        let (series, terminal) = blinker.population_series(4);
+        assert!(!terminal);
+        assert_eq!(series.len(), 5);
+        assert!(series.iter().all(|p| p.population == 3));
+        let generations = series.iter().map(|p| p.generation).collect::<Vec<_>>();
+        assert_eq!(generations, [0, 1, 2, 3, 4]);
+        assert!(series[1..].iter().all(|p| p.delta == 4));
+
+        let mut block = game("....\n.##.\n.##.\n....");
+        let (series, terminal) = block.population_series(10);
+        assert!(terminal);
+        let populations = series.iter().map(|p| p.population).collect::<Vec<_>>();
+        assert_eq!(populations, [4, 4]);
+    }
+
+    #[test]
+    fn random_boards_follow_their_seed() {
+        assert_eq!(random(40, 30, 0.3, 7).grid, random(40, 30, 0.3, 7).grid);
+        assert_ne!(random(40, 30, 0.3, 7).grid, random(40, 30, 0.3, 8).grid);
+
+        let board = random(40, 30, 0.3, 7);
+        assert_eq!((board.rows(), board.cols()), (40, 30));
+        assert_eq!(random(5, 5, 0.0, 7).population(), 0);
+        assert_eq!(random(5, 5, 1.0, 7).population(), 25);
+    }
+
+    #[test]
+    fn run_to_terminal_stops_at_cycles_or_the_cap() {
+        let mut r_pentomino = game(&format!(
+            "{0}\n{0}\n.....##....\n....##.....\n.....#.....\n{0}\n{0}",
+            "..........."
+        ));
+        let report = r_pentomino.run_to_terminal(10);
+        assert_eq!(
+            (report.generation, report.terminal, report.period),
+            (10, false, None)
+        );
+
+        let mut blinker = game(".....\n..#..\n..#..\n..#..\n.....");
+        let report = blinker.run_to_terminal(100);
+        assert_eq!(
+            (report.generation, report.terminal, report.period),
+            (2, false, Some(2))
+        );
+
+        let mut block = game("....\n.##.\n.##.\n....");
+        let report = block.run_to_terminal(100);
+        assert_eq!(
+            serde_json::to_value(report).unwrap(),
+            serde_json::json!({ "generation": 1, "delta": 0, "terminal": true, "period": 1 })
+        );
+    }
+
+    #[test]
+    fn resets_to_the_seed() {
+        let seed = board(".#...\n..#..\n###..\n.....\n.....");
+        let mut game = Game::from(seed.clone());
+        game.step_n(6);
+        let stored = serde_json::to_string(&game).unwrap();
+        let mut game: Game = serde_json::from_str(&stored).unwrap();
+        assert_ne!(game.board.grid, seed.grid);
+
+        game.reset();
+        assert_eq!(game.board.grid, seed.grid);
+        assert_eq!((game.generation, game.delta), (0, 0));
+
+        // games stored before seeds were kept reset to where they were first
+        // stepped from
+        let mut game = Game::from(seed.clone());
+        game.step_n(2);
+        game.seed = None;
+        let stepped_from = game.board.clone();
+        game.keep_seed();
+        game.step_n(3);
+        game.reset();
+        assert_eq!(game.board.grid, stepped_from.grid);
+        assert_eq!(game.generation, 0);
+    }
+
+    #[test]
+    fn parses_macrocells() {
+        let two_gliders = "[M2] (golly 4.2)\n#R B3/S23\n.*$..*$***$\n4 1 0 0 1\n";
+        let board = Board::from_macrocell(two_gliders).unwrap();
+        assert_eq!((board.rows(), board.cols()), (11, 11));
+        let glider = [(0, 1), (1, 2), (2, 0), (2, 1), (2, 2)];
+        let expected = glider
+            .iter()
+            .chain(&glider.map(|(row, col)| (row + 8, col + 8)))
+            .copied()
+            .collect::<Vec<_>>();
+        let live = (0..11)
+            .flat_map(|row| (0..11).map(move |col| (row, col)))
+            .filter(|&(row, col)| board.grid[row][col])
+            .collect::<Vec<_>>();
+        assert_eq!(live, expected);
+
+        assert!(matches!(
+            Board::from_macrocell("[M2]\n.*$..*$***$\n4 1 0 0 2\n"),
+            Err(BoardError::InvalidMacrocell(reason)) if reason == "line 3: undefined node 2"
+        ));
+        assert!(Board::from_macrocell(".*$..*$***$\n").is_err());
+    }
+
+    #[test]
+    fn noise_follows_its_seed() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let noisy = |seed: u64| {
+            let mut game = Game::from(random(20, 20, 0.3, 1));
+            let mut rng = StdRng::seed_from_u64(seed);
+            for _ in 0..5 {
+                game.next_noisy(0.05, &mut rng);
+            }
+            game
+        };
+        assert_eq!(noisy(3).board.grid, noisy(3).board.grid);
+        assert_eq!(noisy(3).delta, noisy(3).delta);
+        assert_ne!(noisy(3).board.grid, noisy(4).board.grid);
+
+        // without noise it's an ordinary step
+        let mut quiet = Game::from(random(20, 20, 0.3, 1));
+        let mut stepped = quiet.clone();
+        quiet.next_noisy(0.0, &mut StdRng::seed_from_u64(3));
+        stepped.next();
+        assert_eq!(quiet.board.grid, stepped.board.grid);
+        assert_eq!(quiet.delta, stepped.delta);
+    }
+
+    #[test]
+    fn async_updates_follow_their_seed() {
+        use rand::{rngs::StdRng, SeedableRng};
+        let stepped = |order: UpdateOrder, seed: u64| {
+            let mut game = Game::from(random(20, 20, 0.3, 1));
+            let mut rng = StdRng::seed_from_u64(seed);
+            for _ in 0..5 {
+                game.next_async(order, &mut rng);
+            }
+            game.board.grid
+        };
+        for order in [UpdateOrder::Sequential, UpdateOrder::Random] {
+            assert_eq!(stepped(order, 3), stepped(order, 3));
+        }
+        // sequential updates never draw from the rng
+        assert_eq!(
+            stepped(UpdateOrder::Sequential, 3),
+            stepped(UpdateOrder::Sequential, 4)
+        );
+        assert_ne!(
+            stepped(UpdateOrder::Random, 3),
+            stepped(UpdateOrder::Random, 4)
+        );
+
+        // later cells see earlier updates: the blinker's top cell dies first,
+        // leaving too few neighbors for the rest to survive or be born
+        let mut blinker = board(".....\n..#..\n..#..\n..#..\n.....");
+        blinker.next_async(UpdateOrder::Sequential, &mut StdRng::seed_from_u64(0));
+        assert_eq!(blinker.population(), 0);
+    }
+
+    #[test]
+    fn explicit_dimensions_pad_or_reject_seeds() {
+        let sized = |rows, cols, strict_dimensions| SeedOptions {
+            rows,
+            cols,
+            strict_dimensions,
+            ..Default::default()
+        };
+
+        let padded =
+            Board::from_seed("#.\n.#".to_string(), sized(Some(3), Some(4), false)).unwrap();
+        assert_eq!(padded.stringify(None, None, None), "#...\n.#..\n....");
+
+        // only the given side is overridden
+        let wide = Board::from_seed("#.\n.#".to_string(), sized(None, Some(3), false)).unwrap();
+        assert_eq!((wide.rows(), wide.cols()), (2, 3));
+
+        // seeds are never truncated to fit
+        assert!(matches!(
+            Board::from_seed("###\n.#.".to_string(), sized(Some(2), Some(2), false)),
+            Err(BoardError::ExceedsDimensions {
+                seed_rows: 2,
+                seed_cols: 3,
+                rows: 2,
+                cols: 2,
+            })
+        ));
+
+        assert!(matches!(
+            Board::from_seed("##\n#".to_string(), sized(Some(4), Some(4), true)),
+            Err(BoardError::RaggedRows {
+                expected: 2,
+                found: 1,
+                row: 1,
+            })
+        ));
+        assert!(Board::from_seed("##\n#".to_string(), sized(Some(4), Some(4), false)).is_ok());
+
+        assert!(matches!(
+            Board::from_seed("#".to_string(), sized(Some(MAX_CELLS), Some(2), false)),
+            Err(BoardError::DimensionsTooLarge { .. })
+        ));
     }
 }
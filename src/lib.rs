@@ -1,13 +1,49 @@
 pub mod game;
 pub mod render;
 
-use game::{Board, Game};
-use http::{header, HeaderMap, HeaderValue, StatusCode};
-use render::{SVGOptions, TextOptions};
-use serde::Deserialize;
+use base64::{
+    alphabet,
+    engine::{DecodePaddingMode, GeneralPurpose, GeneralPurposeConfig},
+    Engine,
+};
+use futures_util::{
+    future::{self, Either},
+    stream, StreamExt,
+};
+use game::{
+    Axis, Board, BoardError, Boundary, CycleDetector, Game, Rule, SeedOptions, UpdateOrder,
+    MAX_COLS, MAX_ROWS,
+};
+use http::{header, HeaderValue, StatusCode};
+use rand::{rngs::StdRng, SeedableRng};
+use render::{
+    AnsiOptions, OptionsError, RenderFormat, RenderOptions, SVGOptions, TextOptions, MAX_FRAMES,
+};
+use serde::{de, Deserialize, Deserializer, Serialize};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::str::FromStr;
+use std::time::Duration;
 use worker::*;
+use xxhash_rust::xxh64::xxh64;
 
 const KV_NAMESPACE: &str = "games";
+const MAX_RUN: usize = 10_000;
+const MAX_STREAM: usize = 100_000;
+const MAX_STEPS: usize = 1_000;
+const MAX_NAME_LENGTH: usize = 64;
+const MAX_MONTAGE: usize = 16;
+const MAX_LIST: usize = 100;
+const MAX_BATCH: usize = 100;
+// generations stepped looking for a cycle with detect_cycle, and remembered
+// by a watched game to stop once it cycles
+const PERIOD_LOOKBACK: usize = 100;
+// generations kept per game to rewind to
+const HISTORY_DEPTH: usize = 10;
+// shortest expiry KV accepts, in seconds
+const MIN_TTL: u64 = 60;
+/// KV allows one write a second to a key, which a watched game is stepped in.
+const MIN_WATCH_INTERVAL: u64 = 1_000;
+const MAX_WATCH_INTERVAL: u64 = 60_000;
 
 macro_rules! fail {
     ($c:expr, $e:expr) => {
@@ -15,133 +51,2015 @@ macro_rules! fail {
     };
 }
 
-macro_rules! build_headers {
-    ($($k:expr => $v:expr),*) => {
+macro_rules! find {
+    ($kv:expr, $name:expr) => {
+        match $kv.get($name).json::<Game>().await {
+            // KV only removes expired keys eventually, until then they're
+            // known to be gone rather than never created
+            Ok(Some(g)) if g.is_expired(now()) => {
+                fail!(StatusCode::GONE, format!("game '{}' has expired", $name))
+            }
+            Ok(Some(g)) => g,
+            Ok(None) => fail!(
+                StatusCode::NOT_FOUND,
+                format!("game '{}' does not exist", $name)
+            ),
+            Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+        }
+    };
+}
+
+/// Reads a setting from the worker's vars, falling back to `default` when it
+/// is unset or fails to parse.
+fn config<T: FromStr>(env: &Env, key: &str, default: T) -> T {
+    env.var(key)
+        .ok()
+        .and_then(|v| v.to_string().parse().ok())
+        .unwrap_or(default)
+}
+
+/// The current time in unix seconds.
+fn now() -> u64 {
+    Date::now().as_millis() / 1000
+}
+
+/// Limits the rows and columns of a seed to the `MAX_ROWS` and `MAX_COLS`
+/// vars.
+fn limit_seed(env: &Env, opts: SeedOptions) -> SeedOptions {
+    SeedOptions {
+        max_rows: config(env, "MAX_ROWS", MAX_ROWS),
+        max_cols: config(env, "MAX_COLS", MAX_COLS),
+        ..opts
+    }
+}
+
+/// Holds boards built from explicit dimensions rather than a seed to the
+/// same limits as seeds.
+fn limit_dimensions(env: &Env, rows: usize, cols: usize) -> std::result::Result<(), BoardError> {
+    check_dimensions(&limit_seed(env, SeedOptions::default()), rows, cols)
+}
+
+fn check_dimensions(
+    limits: &SeedOptions,
+    rows: usize,
+    cols: usize,
+) -> std::result::Result<(), BoardError> {
+    if rows > limits.max_rows || cols > limits.max_cols {
+        return Err(BoardError::DimensionsTooLarge {
+            rows,
+            cols,
+            max_rows: limits.max_rows,
+            max_cols: limits.max_cols,
+        });
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str, max_len: usize) -> std::result::Result<(), String> {
+    if !name.chars().all(|c| c.is_alphanumeric() || c == '-') {
+        return Err("game name must be alphanumeric or '-'".to_string());
+    }
+
+    if name.chars().count() > max_len {
+        return Err(format!("game name must be at most {} characters", max_len));
+    }
+
+    Ok(())
+}
+
+/// Deserializes a separator from either a single character or one of the named
+/// tokens `newline`, `tab`, `pipe` or `comma`, since some clients mangle raw
+/// control characters in query strings.
+fn separator<'de, D>(deserializer: D) -> std::result::Result<Option<char>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(value) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    parse_separator(&value).map(Some).map_err(de::Error::custom)
+}
+
+fn parse_separator(value: &str) -> std::result::Result<char, String> {
+    let separator = match value {
+        "newline" => '\n',
+        "tab" => '\t',
+        "pipe" => '|',
+        "comma" => ',',
+        _ => {
+            let mut chars = value.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => c,
+                _ => {
+                    return Err(format!(
+                        "invalid separator: '{}', expected a single character or newline, tab, pipe, comma",
+                        value
+                    ))
+                }
+            }
+        }
+    };
+
+    Ok(separator)
+}
+
+/// Deserializes a CSS color like `red` or `#ff0000` into its RGB channels.
+fn color<'de, D>(deserializer: D) -> std::result::Result<Option<[u8; 3]>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(value) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    render::parse_color(&value)
+        .map(Some)
+        .map_err(|c| de::Error::custom(format!("invalid color: '{}'", c)))
+}
+
+/// Deserializes two CSS colors split by a comma, like `yellow,red`, into the
+/// RGB channels of each.
+fn gradient<'de, D>(deserializer: D) -> std::result::Result<Option<render::Gradient>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(value) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    let Some((from, to)) = value.split_once(',') else {
+        return Err(de::Error::custom(format!(
+            "invalid gradient: '{}', expected two colors split by a comma",
+            value
+        )));
+    };
+    let parse = |c: &str| {
+        render::parse_color(c.trim())
+            .map_err(|c| de::Error::custom(format!("invalid color: '{}'", c)))
+    };
+    Ok(Some((parse(from)?, parse(to)?)))
+}
+
+/// Separator of a submitted seed, either given or guessed from the body.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SeedSeparator {
+    Char(char),
+    Auto,
+}
+
+/// Like [`separator`], but also accepts `auto` to detect it from the seed.
+fn seed_separator<'de, D>(deserializer: D) -> std::result::Result<Option<SeedSeparator>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let Some(value) = Option::<String>::deserialize(deserializer)? else {
+        return Ok(None);
+    };
+
+    if value == "auto" {
+        return Ok(Some(SeedSeparator::Auto));
+    }
+
+    parse_separator(&value)
+        .map(|c| Some(SeedSeparator::Char(c)))
+        .map_err(de::Error::custom)
+}
+
+#[derive(Deserialize, Debug, Default, Clone)]
+struct RenderParams {
+    next: Option<bool>,
+    steps: Option<usize>,
+    alive: Option<char>,
+    dead: Option<char>,
+    #[serde(default, deserialize_with = "separator")]
+    separator: Option<char>,
+    cell_size: Option<usize>,
+    stroke_width: Option<usize>,
+    stroke_color: Option<String>,
+    fill_color: Option<String>,
+    precision: Option<usize>,
+    noise: Option<f64>,
+    rng_seed: Option<u64>,
+    delta_only: Option<bool>,
+    title: Option<String>,
+    update: Option<UpdateOrder>,
+    ruler: Option<bool>,
+    ruler_step: Option<usize>,
+    activity: Option<usize>,
+    frames: Option<usize>,
+    frame_duration: Option<usize>,
+    delay: Option<usize>,
+    detect_cycle: Option<bool>,
+    #[serde(default, deserialize_with = "color")]
+    fg: Option<[u8; 3]>,
+    #[serde(default, deserialize_with = "color")]
+    bg: Option<[u8; 3]>,
+    plain: Option<bool>,
+    crop: Option<bool>,
+    margin: Option<usize>,
+    caption: Option<bool>,
+    caption_template: Option<String>,
+    font_family: Option<String>,
+    font_size: Option<usize>,
+    #[serde(default, deserialize_with = "gradient")]
+    age_gradient: Option<render::Gradient>,
+}
+
+impl From<RenderParams> for SVGOptions {
+    fn from(p: RenderParams) -> Self {
+        let ruler = p.ruler.unwrap_or(false);
+        let opts = SVGOptions::new(
+            p.cell_size,
+            p.stroke_width,
+            p.stroke_color,
+            p.fill_color,
+            p.precision,
+        );
+        SVGOptions {
+            title: p.title,
+            ruler: ruler.then(|| p.ruler_step.unwrap_or(5).max(1)),
+            frame_duration: p.frame_duration.unwrap_or(250).max(1),
+            caption: p.caption.unwrap_or(true),
+            caption_template: p.caption_template,
+            font_family: p.font_family.unwrap_or(opts.font_family),
+            font_size: p.font_size.unwrap_or(opts.font_size),
+            age_gradient: p.age_gradient,
+            ..opts
+        }
+    }
+}
+
+impl RenderParams {
+    fn validate(&self) -> std::result::Result<(), OptionsError> {
+        SVGOptions::validate(
+            self.cell_size,
+            self.stroke_width,
+            self.precision,
+            self.font_size,
+        )
+    }
+
+    fn into_options(self, name: &str) -> RenderOptions {
+        RenderOptions {
+            name: name.to_string(),
+            activity: self.activity,
+            frames: self.frames,
+            delay: self.delay,
+            text: TextOptions::new(self.alive, self.dead, self.separator),
+            ansi: AnsiOptions::new(self.fg, self.bg, self.plain),
+            svg: self.into(),
+        }
+    }
+}
+
+async fn render(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let (name, ext) = match name.rsplit_once('.') {
+        Some((name, ext)) => (name, Some(ext)),
+        None => (name.as_str(), None),
+    };
+    let (name, ext) = match name.strip_suffix(".anim") {
+        Some(name) if ext == Some("svg") => (name, Some("anim.svg")),
+        _ => (name, ext),
+    };
+    // an extension wins over the Accept header
+    let negotiated = ext.is_none();
+    let accept = req.headers().get(header::ACCEPT.as_str())?;
+    let ext = RenderFormat::negotiate(accept.as_deref(), ext).ext();
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut game = find!(kv, name);
+
+    let params = match req.query::<RenderParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    if let Err(e) = params.validate() {
+        fail!(StatusCode::BAD_REQUEST, e);
+    }
+
+    if params.noise.is_some_and(|p| !(0.0..=1.0).contains(&p)) {
+        fail!(StatusCode::BAD_REQUEST, "noise must be between 0 and 1");
+    }
+
+    if let Some(activity) = params.activity {
+        if ext != "svg" {
+            fail!(
+                StatusCode::BAD_REQUEST,
+                "activity is only supported for svg"
+            );
+        }
+        if !(1..=MAX_RUN).contains(&activity) {
+            fail!(
+                StatusCode::BAD_REQUEST,
+                format!("activity must be between 1 and {}", MAX_RUN)
+            );
+        }
+    }
+
+    match params.frames {
+        Some(0) => fail!(StatusCode::BAD_REQUEST, "frames must be at least 1"),
+        Some(f) if f > MAX_FRAMES => fail!(
+            StatusCode::PAYLOAD_TOO_LARGE,
+            format!("frames must be at most {}", MAX_FRAMES)
+        ),
+        _ => {}
+    }
+
+    if params.noise.is_some() && params.update.is_some() {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            "noise and update cannot be combined"
+        );
+    }
+
+    let crop = params.crop.unwrap_or(false);
+    if crop && params.delta_only.unwrap_or(false) {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            "delta_only and crop cannot be combined"
+        );
+    }
+
+    // next=true is a single step
+    let steps = params
+        .steps
+        .unwrap_or(params.next.unwrap_or(false) as usize);
+    // animations play the generations after the stored one and never save, so
+    // stepping is ignored for them rather than saving a step nobody sees
+    let animated = matches!(
+        RenderFormat::from_ext(ext),
+        RenderFormat::Gif | RenderFormat::AnimatedSvg
+    );
+    let ignored = animated && steps > 0;
+    let steps = if animated { 0 } else { steps };
+    let max_steps = config(&ctx.env, "MAX_STEPS", MAX_STEPS);
+    if steps > max_steps {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("steps must be at most {}", max_steps)
+        );
+    }
+
+    let stepped = steps > 0;
+    let mut before = (stepped && params.delta_only.unwrap_or(false)).then(|| game.board.clone());
+    let mut taken = 0;
+    if stepped {
+        game.keep_seed();
+        let past = HistoryEntry::from(&game);
+        let mut rng = StdRng::seed_from_u64(params.rng_seed.unwrap_or(game.generation as u64));
+        // stop early once the board stops changing
+        while taken < steps && (taken == 0 || !game.is_terminal()) {
+            // expanding boards grow as they step, the board compared against
+            // grows with them
+            if let Some(before) = before.as_mut() {
+                game.board.grow_like_next(&mut before.grid, false);
+            }
+            match (params.noise, params.update) {
+                (Some(p), _) => game.next_noisy(p, &mut rng),
+                (_, Some(order)) => game.next_async(order, &mut rng),
+                _ => game.next(),
+            }
+            taken += 1;
+        }
+        if let Err(e) = save(&kv, name, &game).await {
+            fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+        }
+        let depth = config(&ctx.env, "HISTORY_DEPTH", HISTORY_DEPTH);
+        remember(&kv, name, past, game.expires_at, depth).await;
+    }
+
+    // counted after the response is sent, so views never slow down renders
+    if !stepped && config(&ctx.env, "VIEW_COUNTER", true) {
+        let (kv, name, expires_at) = (kv.clone(), name.to_string(), game.expires_at);
+        ctx.data
+            .wait_until(async move { count_view(&kv, &name, expires_at).await });
+    }
+
+    console_log!(
+        "render name={} format={} generation={} delta={} population={} steps={}",
+        name,
+        ext,
+        game.generation,
+        game.delta,
+        game.board.population(),
+        taken
+    );
+
+    let mut headers = render::metadata(&game);
+    if negotiated {
+        headers.append(header::VARY, HeaderValue::from_static("accept"));
+    }
+    let compressible = RenderFormat::from_ext(ext).is_compressible();
+    if compressible {
+        headers.append(header::VARY, HeaderValue::from_static("accept-encoding"));
+    }
+    if stepped {
+        headers.insert("x-life-steps", HeaderValue::from(taken));
+    }
+    if ignored {
+        headers.insert(
+            header::WARNING,
+            HeaderValue::from_static("199 - \"next and steps are ignored for animations\""),
+        );
+    }
+
+    if config(&ctx.env, "CLUSTERS_HEADER", true) {
+        headers.insert("x-life-clusters", HeaderValue::from(game.board.clusters()));
+    }
+
+    if params.detect_cycle.unwrap_or(false) {
+        let period = game.detect_period(PERIOD_LOOKBACK).unwrap_or(0);
+        headers.insert("x-life-period", HeaderValue::from(period));
+    }
+
+    // with delta_only, only the area that changed in this step is rendered
+    let game = match before {
+        Some(before) => {
+            let Some((row, col, rows, cols)) = before.changed_bounds(&game.board) else {
+                return Ok(ResponseBuilder::new()
+                    .with_headers(headers.into())
+                    .with_status(StatusCode::NO_CONTENT.into())
+                    .empty());
+            };
+            headers.insert("x-life-offset-row", HeaderValue::from(row));
+            headers.insert("x-life-offset-col", HeaderValue::from(col));
+            Game {
+                board: game.board.region(row, col, rows, cols),
+                ..game
+            }
+        }
+        // with crop, only the live cells are rendered
+        None if crop => {
+            let mut game = game;
+            let (row, col) = game.board.crop_to_live(params.margin.unwrap_or(0));
+            headers.insert("x-life-offset-row", HeaderValue::from(row));
+            headers.insert("x-life-offset-col", HeaderValue::from(col));
+            game
+        }
+        None => game,
+    };
+
+    let etag = etag(&game, &req.url()?, ext);
+    let if_none_match = req.headers().get(header::IF_NONE_MATCH.as_str())?;
+    if is_not_modified(stepped, if_none_match.as_deref(), &etag) {
+        return Ok(ResponseBuilder::new()
+            .with_headers(headers.into())
+            .with_header(header::ETAG.as_str(), &etag)?
+            .with_status(StatusCode::NOT_MODIFIED.into())
+            .empty());
+    }
+
+    let mut res = ResponseBuilder::new()
+        .with_headers(headers.into())
+        .with_header(header::ETAG.as_str(), &etag)?;
+
+    // the runtime compresses the body to match its content-encoding, so it's
+    // only named here
+    let accept_encoding = req.headers().get(header::ACCEPT_ENCODING.as_str())?;
+    if let Some(encoding) = accept_encoding
+        .as_deref()
+        .and_then(content_encoding)
+        .filter(|_| compressible)
+    {
+        res = res.with_header(header::CONTENT_ENCODING.as_str(), encoding)?;
+    }
+
+    respond(res, name, &game, ext, params)
+}
+
+/// The best encoding the runtime can compress with that an
+/// `Accept-Encoding` header allows, preferring brotli over gzip.
+fn content_encoding(accept_encoding: &str) -> Option<&'static str> {
+    let mut best: Option<(&'static str, f32)> = None;
+    for coding in accept_encoding.split(',') {
+        let mut parts = coding.split(';').map(str::trim);
+        let encoding = match parts.next() {
+            Some("br") => "br",
+            Some("gzip") => "gzip",
+            _ => continue,
+        };
+        let q = match parts.find_map(|p| p.strip_prefix("q=")) {
+            Some(q) => q.parse().unwrap_or(0.0),
+            None => 1.0,
+        };
+        let better =
+            best.is_none_or(|(best, best_q)| q > best_q || (q == best_q && best == "gzip"));
+        if q > 0.0 && better {
+            best = Some((encoding, q));
+        }
+    }
+
+    best.map(|(encoding, _)| encoding)
+}
+
+/// Renders the game in the format for `ext`, falling back to text. The body
+/// is sent with the game's metadata and an `x-life-checksum` header (hex
+/// xxh64, seed 0) so clients can verify they received all of it.
+fn respond(
+    mut res: ResponseBuilder,
+    name: &str,
+    game: &Game,
+    ext: &str,
+    params: RenderParams,
+) -> Result<Response> {
+    let format = RenderFormat::from_ext(ext);
+    let (body, headers) = match game.render_with_headers(format, params.into_options(name)) {
+        Ok(rendered) => rendered,
+        Err(e) => fail!(e.status_code(), e),
+    };
+
+    for (key, value) in &headers {
+        let value = value.to_str().expect("rendered headers are ASCII");
+        res = res.with_header(key.as_str(), value)?;
+    }
+    Ok(res.fixed(body))
+}
+
+/// RLE patterns start with an `x = ..` header, possibly after `#` comments.
+fn is_rle(body: &str) -> bool {
+    body.lines()
+        .map(str::trim_start)
+        .find(|l| !l.starts_with('#'))
+        .is_some_and(|l| l.starts_with('x') && l[1..].trim_start().starts_with('='))
+}
+
+/// Whether a game is stored under `name` and hasn't expired, KV only removes
+/// expired keys eventually. Games that can't be read still count, so they're
+/// never overwritten by accident.
+async fn exists(kv: &kv::KvStore, name: &str) -> bool {
+    match kv.get(name).text().await {
+        Ok(Some(game)) => {
+            serde_json::from_str::<Game>(&game).map_or(true, |g| !g.is_expired(now()))
+        }
+        _ => false,
+    }
+}
+
+/// Kept as the KV metadata of each game so listing them doesn't need to read
+/// every board.
+#[derive(Serialize, Deserialize, Debug)]
+struct GameSummary {
+    generation: usize,
+    delta: usize,
+    rows: usize,
+    cols: usize,
+}
+
+impl From<&Game> for GameSummary {
+    fn from(game: &Game) -> Self {
+        GameSummary {
+            generation: game.generation,
+            delta: game.delta,
+            rows: game.board.rows(),
+            cols: game.board.cols(),
+        }
+    }
+}
+
+/// Stores a game along with its [`GameSummary`], expiring it from KV along
+/// with the game.
+async fn save(kv: &kv::KvStore, name: &str, game: &Game) -> std::result::Result<(), kv::KvError> {
+    let put = kv.put(name, game)?.metadata(GameSummary::from(game))?;
+    match game.expires_at {
+        Some(at) => put.expiration(expiration(at)),
+        None => put,
+    }
+    .execute()
+    .await
+}
+
+/// The KV expiration for a game expiring `at`. KV rejects expirations less
+/// than [`MIN_TTL`] away, by then the game reads as gone anyway.
+fn expiration(at: u64) -> u64 {
+    at.max(now() + MIN_TTL)
+}
+
+/// Views are counted under their own key so bumping them never rewrites (or
+/// races with) the game itself.
+fn views_key(name: &str) -> String {
+    format!("views:{}", name)
+}
+
+async fn views(kv: &kv::KvStore, name: &str) -> u64 {
+    match kv.get(&views_key(name)).text().await {
+        Ok(Some(v)) => v.parse().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Best-effort view count bump: KV has no atomic increment, so concurrent
+/// views may be lost, and failures are only logged.
+async fn count_view(kv: &kv::KvStore, name: &str, expires_at: Option<u64>) {
+    let count = views(kv, name).await + 1;
+    let put = match kv.put(&views_key(name), count) {
+        Ok(put) => {
+            match expires_at {
+                Some(at) => put.expiration(expiration(at)),
+                None => put,
+            }
+            .execute()
+            .await
+        }
+        Err(e) => Err(e),
+    };
+    if let Err(e) = put {
+        console_log!("count_view name={} error={}", name, e);
+    }
+}
+
+/// A past generation of a game to rewind to, with the board as RLE to keep
+/// the history small.
+#[derive(Serialize, Deserialize, Debug)]
+struct HistoryEntry {
+    generation: usize,
+    delta: usize,
+    board: String,
+}
+
+impl From<&Game> for HistoryEntry {
+    fn from(game: &Game) -> Self {
+        HistoryEntry {
+            generation: game.generation,
+            delta: game.delta,
+            board: render::rle(game),
+        }
+    }
+}
+
+impl HistoryEntry {
+    /// Puts the game back to this generation, keeping its boundary, which
+    /// RLE doesn't record.
+    fn restore(self, game: &mut Game) -> std::result::Result<(), BoardError> {
+        let mut board = Board::from_rle(&self.board)?;
+        board.boundary = game.board.boundary;
+        game.board = board;
+        game.generation = self.generation;
+        game.delta = self.delta;
+        Ok(())
+    }
+}
+
+/// Past generations are kept under their own key, oldest first, so stepping
+/// games that are never rewound doesn't grow them.
+fn history_key(name: &str) -> String {
+    format!("history:{}", name)
+}
+
+async fn history(kv: &kv::KvStore, name: &str) -> Vec<HistoryEntry> {
+    match kv.get(&history_key(name)).json().await {
+        Ok(Some(history)) => history,
+        _ => Vec::new(),
+    }
+}
+
+async fn save_history(
+    kv: &kv::KvStore,
+    name: &str,
+    history: &[HistoryEntry],
+    expires_at: Option<u64>,
+) -> std::result::Result<(), kv::KvError> {
+    let put = kv.put(&history_key(name), history)?;
+    match expires_at {
+        Some(at) => put.expiration(expiration(at)),
+        None => put,
+    }
+    .execute()
+    .await
+}
+
+/// Adds a generation to the history of a game, keeping the last `depth`.
+/// Generations at or after it are from a timeline that was rewound or reset,
+/// so they're dropped. Like views this is best-effort.
+async fn remember(
+    kv: &kv::KvStore,
+    name: &str,
+    past: HistoryEntry,
+    expires_at: Option<u64>,
+    depth: usize,
+) {
+    if depth == 0 {
+        return;
+    }
+
+    let mut history = history(kv, name).await;
+    push_history(&mut history, past, depth);
+    if let Err(e) = save_history(kv, name, &history, expires_at).await {
+        console_log!("remember name={} error={}", name, e);
+    }
+}
+
+/// The SVG the viewer of `name` fetches each frame from: the viewer's own
+/// query, stepping once per fetch.
+fn frame_url(mut url: Url, name: &str) -> String {
+    let pairs = url
+        .query_pairs()
+        .filter(|(k, _)| k != "interval" && k != "next" && k != "steps")
+        .map(|(k, v)| (k.into_owned(), v.into_owned()))
+        .collect::<Vec<_>>();
+    url.set_path(&format!("/{}.svg", name));
+    url.query_pairs_mut()
+        .clear()
+        .extend_pairs(pairs)
+        .append_pair("next", "true");
+    format!("{}?{}", url.path(), url.query().unwrap_or_default())
+}
+
+/// Adds `past` to a history kept to the last `depth` generations, dropping
+/// any at or after it.
+fn push_history(history: &mut Vec<HistoryEntry>, past: HistoryEntry, depth: usize) {
+    history.retain(|entry| entry.generation < past.generation);
+    history.push(past);
+    let excess = history.len().saturating_sub(depth);
+    history.drain(..excess);
+}
+
+/// Fingerprints a rendered game: the board contents and counters, plus the
+/// rendered format and requested options, so any change to the output
+/// changes the tag.
+/// The tag is quoted, as entity tags are, and starts with the generation so
+/// it's clear which one a client has.
+fn etag(game: &Game, url: &Url, ext: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    game.hash(&mut hasher);
+    // ages can be drawn but aren't part of the board's hash
+    game.board.age.hash(&mut hasher);
+    // the format may come from the Accept header rather than the path
+    ext.hash(&mut hasher);
+    url.path().hash(&mut hasher);
+    url.query().hash(&mut hasher);
+    format!("\"{}-{:x}\"", game.generation, hasher.finish())
+}
+
+/// Whether an `If-None-Match` header lists `etag` or is `*`. Weak tags match
+/// their strong counterpart, as the header compares weakly.
+fn matches_etag(if_none_match: &str, etag: &str) -> bool {
+    if_none_match
+        .split(',')
+        .map(str::trim)
+        .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag)
+}
+
+/// Whether a render can be answered with `304 Not Modified`. Stepping always
+/// renders a new generation, so only plain renders can be served from the
+/// client's copy.
+fn is_not_modified(stepped: bool, if_none_match: Option<&str>, etag: &str) -> bool {
+    !stepped && if_none_match.is_some_and(|tags| matches_etag(tags, etag))
+}
+
+#[derive(Deserialize, Debug)]
+struct CreatorParams {
+    alive: Option<char>,
+    dead: Option<char>,
+    #[serde(default, deserialize_with = "seed_separator")]
+    separator: Option<SeedSeparator>,
+    trim_input: Option<bool>,
+    lenient: Option<bool>,
+    format: Option<String>,
+    input: Option<String>,
+    rows: Option<usize>,
+    cols: Option<usize>,
+    overwrite: Option<bool>,
+    random: Option<bool>,
+    density: Option<f64>,
+    rng_seed: Option<u64>,
+    rule: Option<String>,
+    wrap: Option<bool>,
+    auto_expand: Option<bool>,
+    strict: Option<bool>,
+    ttl: Option<u64>,
+}
+
+impl From<CreatorParams> for SeedOptions {
+    fn from(p: CreatorParams) -> Self {
+        let separator = match p.separator {
+            Some(SeedSeparator::Char(c)) => Some(c),
+            Some(SeedSeparator::Auto) | None => None,
+        };
+        SeedOptions {
+            rows: p.rows,
+            cols: p.cols,
+            strict_dimensions: p.strict.unwrap_or(false),
+            ..SeedOptions::new(p.alive, p.dead, separator, p.trim_input, p.lenient)
+        }
+    }
+}
+
+async fn create(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let params = match req.query::<CreatorParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    create_game(req, ctx, params).await
+}
+
+#[derive(Deserialize, Debug)]
+struct RandomParams {
+    seed: Option<u64>,
+}
+
+/// `POST /:name?random=true` as its own route, with `seed` as a shorter
+/// `rng_seed`.
+async fn random(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let mut params = match req.query::<CreatorParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let seed = match req.query::<RandomParams>() {
+        Ok(p) => p.seed,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    params.random = Some(true);
+    params.rng_seed = seed.or(params.rng_seed);
+    create_game(req, ctx, params).await
+}
+
+async fn create_game(
+    mut req: Request,
+    ctx: RouteContext<Context>,
+    mut params: CreatorParams,
+) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let max_name_length = config(&ctx.env, "MAX_NAME_LENGTH", MAX_NAME_LENGTH);
+    if let Err(e) = validate_name(name, max_name_length) {
+        fail!(StatusCode::BAD_REQUEST, e);
+    }
+
+    let boundary = match (
+        params.wrap.unwrap_or(false),
+        params.auto_expand.unwrap_or(false),
+    ) {
+        (true, true) => fail!(
+            StatusCode::BAD_REQUEST,
+            "wrap and auto_expand cannot be combined"
+        ),
+        (true, false) => Boundary::Toroidal,
+        (false, true) => Boundary::Expanding,
+        (false, false) => Boundary::Dead,
+    };
+    let ttl = params.ttl;
+    if ttl.is_some_and(|ttl| ttl < MIN_TTL) {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("ttl must be at least {} seconds", MIN_TTL)
+        );
+    }
+
+    let rule = match params.rule.take().map(|r| Rule::parse(&r)).transpose() {
+        Ok(rule) => rule,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let accept = req.headers().get(header::ACCEPT.as_str())?;
+    let content_type = req.headers().get(header::CONTENT_TYPE.as_str())?;
+    let if_none_match = req.headers().get(header::IF_NONE_MATCH.as_str())?;
+    let overwrite = params.overwrite.unwrap_or(false);
+    // like rendering, the Accept header picks the format unless one is given
+    let format = params.format.take().unwrap_or_else(|| {
+        RenderFormat::negotiate(accept.as_deref(), None)
+            .ext()
+            .to_string()
+    });
+
+    let body = match req.text().await {
+        Ok(b) => b,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let board = match params.input.take().as_deref() {
+        _ if params.random.unwrap_or(false) => {
+            let (Some(rows), Some(cols)) = (params.rows, params.cols) else {
+                fail!(
+                    StatusCode::BAD_REQUEST,
+                    "rows and cols are required for random boards"
+                );
+            };
+            let density = params.density.unwrap_or(0.5);
+            if !(0.0..=1.0).contains(&density) {
+                fail!(StatusCode::BAD_REQUEST, "density must be between 0 and 1");
+            }
+            if let Err(e) = limit_dimensions(&ctx.env, rows, cols) {
+                fail!(e.status_code(), e);
+            }
+            // seeded by the name so re-creating a game gives the same board
+            let seed = params.rng_seed.unwrap_or_else(|| xxh64(name.as_bytes(), 0));
+            Board::random(rows, cols, density, &mut StdRng::seed_from_u64(seed))
+        }
+        Some("centered") => {
+            let coords = match serde_json::from_str::<Vec<(i32, i32)>>(&body) {
+                Ok(c) => c,
+                Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+            };
+            let (Some(rows), Some(cols)) = (params.rows, params.cols) else {
+                fail!(
+                    StatusCode::BAD_REQUEST,
+                    "rows and cols are required for centered input"
+                );
+            };
+            if let Err(e) = limit_dimensions(&ctx.env, rows, cols) {
+                fail!(e.status_code(), e);
+            }
+            Board::from_centered_coords(&coords, rows, cols)
+        }
+        Some("cells") => Board::from_cells(&body),
+        Some(input) => fail!(
+            StatusCode::BAD_REQUEST,
+            format!("unknown input format: '{}'", input)
+        ),
+        None if body.starts_with("[M2]") => Board::from_macrocell(&body),
+        None if content_type
+            .as_ref()
+            .is_some_and(|c| c.starts_with("text/x-cells"))
+            || body.starts_with("!Name:") =>
+        {
+            Board::from_cells(&body)
+        }
+        None if content_type
+            .as_ref()
+            .is_some_and(|c| c.starts_with("text/x-life106"))
+            || body.starts_with("#Life 1.06") =>
         {
-            let mut map = HeaderMap::new();
-            $(map.insert($k, HeaderValue::from($v));)*
-            map
+            Board::from_life106(&body)
+        }
+        None if content_type.is_some_and(|c| c.starts_with("text/x-rle")) || is_rle(&body) => {
+            Board::from_rle(&body)
+        }
+        None => {
+            let auto = params.separator == Some(SeedSeparator::Auto);
+            let mut opts = limit_seed(&ctx.env, SeedOptions::from(params));
+            if auto {
+                opts.detect_separator(&body);
+            }
+            Board::from_seed(body, opts)
+        }
+    };
+
+    let mut board = match board {
+        Ok(b) => b,
+        Err(e) => fail!(e.status_code(), e),
+    };
+    // an explicit rule wins over one from the pattern
+    if let Some(rule) = rule {
+        board.rule = rule;
+    }
+    board.boundary = boundary;
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    // `If-None-Match: *` asks to only create, overwrite=true to replace
+    let existed = exists(&kv, name).await;
+    if existed && if_none_match.is_some_and(|v| v.trim() == "*") {
+        fail!(
+            StatusCode::PRECONDITION_FAILED,
+            format!("game '{}' already exists", name)
+        );
+    }
+
+    if existed && !overwrite {
+        fail!(
+            StatusCode::CONFLICT,
+            format!("game '{}' already exists", name)
+        );
+    }
+
+    // a replaced game starts over, without the old one's views and history
+    if existed {
+        for key in [views_key(name), history_key(name)] {
+            if let Err(e) = kv.delete(&key).await {
+                fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+            }
+        }
+    }
+
+    let now = now();
+    let game = Game {
+        created_at: Some(now),
+        expires_at: ttl.map(|ttl| now.saturating_add(ttl)),
+        ..Game::from(board)
+    };
+    if let Err(e) = save(&kv, name, &game).await {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    let status = match existed {
+        true => StatusCode::OK,
+        false => StatusCode::CREATED,
+    };
+    let res = ResponseBuilder::new().with_status(status.into());
+    respond(res, name, &game, &format, Default::default())
+}
+
+#[derive(Deserialize, Debug)]
+struct BatchItem {
+    name: String,
+    seed: String,
+    alive: Option<char>,
+    dead: Option<char>,
+    #[serde(default, deserialize_with = "separator")]
+    separator: Option<char>,
+}
+
+/// What happened to one game of a batch: `created`, `conflict` if the name
+/// was taken, or `error` if it couldn't be saved.
+#[derive(Serialize, Debug)]
+struct BatchResult {
+    name: String,
+    status: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+/// Creates several games from seeds at once. Every item is validated before
+/// anything is saved, so one bad name or seed fails the whole batch. KV has
+/// no transactions though, so after that each game is saved on its own and
+/// taken names are reported as conflicts rather than failing the rest.
+async fn batch(mut req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let items = match req.json::<Vec<BatchItem>>().await {
+        Ok(items) => items,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    if !(1..=MAX_BATCH).contains(&items.len()) {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("batch must have between 1 and {} games", MAX_BATCH)
+        );
+    }
+
+    let max_name_length = config(&ctx.env, "MAX_NAME_LENGTH", MAX_NAME_LENGTH);
+    let mut games = Vec::with_capacity(items.len());
+    for (i, item) in items.into_iter().enumerate() {
+        if let Err(e) = validate_name(&item.name, max_name_length) {
+            fail!(StatusCode::BAD_REQUEST, format!("game {}: {}", i, e));
+        }
+        if games.iter().any(|(name, _)| *name == item.name) {
+            fail!(
+                StatusCode::BAD_REQUEST,
+                format!("game {}: '{}' is in the batch twice", i, item.name)
+            );
+        }
+
+        let opts = SeedOptions::new(item.alive, item.dead, item.separator, None, None);
+        let opts = limit_seed(&ctx.env, opts);
+        match Board::from_seed(item.seed, opts) {
+            Ok(board) => games.push((
+                item.name,
+                Game {
+                    created_at: Some(now()),
+                    ..Game::from(board)
+                },
+            )),
+            Err(e) => fail!(e.status_code(), format!("game {}: {}", i, e)),
+        }
+    }
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut results = Vec::with_capacity(games.len());
+    for (name, game) in games {
+        let (status, error) = if exists(&kv, &name).await {
+            ("conflict", Some(format!("game '{}' already exists", name)))
+        } else {
+            match save(&kv, &name, &game).await {
+                Ok(()) => ("created", None),
+                Err(e) => ("error", Some(e.to_string())),
+            }
+        };
+        console_log!("batch name={} status={}", name, status);
+        results.push(BatchResult {
+            name,
+            status,
+            error,
+        });
+    }
+
+    Response::from_json(&results)
+}
+
+async fn available(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let max_name_length = config(&ctx.env, "MAX_NAME_LENGTH", MAX_NAME_LENGTH);
+    if let Err(e) = validate_name(name, max_name_length) {
+        fail!(StatusCode::BAD_REQUEST, e);
+    }
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    if exists(&kv, name).await {
+        fail!(
+            StatusCode::CONFLICT,
+            format!("game '{}' already exists", name)
+        );
+    }
+
+    Response::ok(format!("game '{}' is available", name))
+}
+
+async fn delete(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    if !exists(&kv, name).await {
+        fail!(
+            StatusCode::NOT_FOUND,
+            format!("game '{}' does not exist", name)
+        );
+    }
+
+    for key in [name.to_string(), views_key(name), history_key(name)] {
+        if let Err(e) = kv.delete(&key).await {
+            fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+        }
+    }
+
+    console_log!("delete name={}", name);
+
+    Ok(ResponseBuilder::new()
+        .with_status(StatusCode::NO_CONTENT.into())
+        .empty())
+}
+
+#[derive(Deserialize, Debug)]
+struct ForkParams {
+    to: String,
+}
+
+async fn fork(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let params = match req.query::<ForkParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let max_name_length = config(&ctx.env, "MAX_NAME_LENGTH", MAX_NAME_LENGTH);
+    if let Err(e) = validate_name(&params.to, max_name_length) {
+        fail!(StatusCode::BAD_REQUEST, e);
+    }
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    // the fork is a new game, but expires with the original
+    let game = Game {
+        created_at: Some(now()),
+        ..find!(kv, name)
+    };
+
+    if exists(&kv, &params.to).await {
+        fail!(
+            StatusCode::CONFLICT,
+            format!("game '{}' already exists", params.to)
+        );
+    }
+
+    if let Err(e) = save(&kv, &params.to, &game).await {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    let res = ResponseBuilder::new().with_status(StatusCode::CREATED.into());
+    respond(res, &params.to, &game, "txt", Default::default())
+}
+
+async fn reset(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut game = find!(kv, name);
+    game.reset();
+    if let Err(e) = save(&kv, name, &game).await {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    console_log!("reset name={}", name);
+
+    respond(
+        ResponseBuilder::new(),
+        name,
+        &game,
+        "txt",
+        Default::default(),
+    )
+}
+
+#[derive(Deserialize, Debug)]
+struct CropParams {
+    margin: Option<usize>,
+}
+
+async fn crop(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let params = match req.query::<CropParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut game = find!(kv, name);
+    let (row, col) = game.board.crop_to_live(params.margin.unwrap_or(0));
+    if let Err(e) = save(&kv, name, &game).await {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    console_log!(
+        "crop name={} rows={} cols={}",
+        name,
+        game.board.rows(),
+        game.board.cols()
+    );
+
+    let res = ResponseBuilder::new()
+        .with_header("x-life-offset-row", &row.to_string())?
+        .with_header("x-life-offset-col", &col.to_string())?;
+    respond(res, name, &game, "txt", Default::default())
+}
+
+#[derive(Deserialize, Debug)]
+struct RewindParams {
+    to: Option<usize>,
+}
+
+/// Rewinds a game to a generation in its history, the latest one unless
+/// `to` is given. Later generations are dropped from the history.
+async fn rewind(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let params = match req.query::<RewindParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut game = find!(kv, name);
+    let mut history = history(&kv, name).await;
+    let Some(to) = params.to.or(history.last().map(|entry| entry.generation)) else {
+        fail!(
+            StatusCode::NOT_FOUND,
+            format!("game '{}' has no history", name)
+        );
+    };
+    let Some(i) = history.iter().position(|entry| entry.generation == to) else {
+        fail!(
+            StatusCode::NOT_FOUND,
+            format!("generation {} is not in the history of '{}'", to, name)
+        );
+    };
+
+    let past = history.remove(i);
+    history.truncate(i);
+    if let Err(e) = past.restore(&mut game) {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    if let Err(e) = save(&kv, name, &game).await {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+    if let Err(e) = save_history(&kv, name, &history, game.expires_at).await {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    console_log!("rewind name={} generation={}", name, to);
+
+    respond(
+        ResponseBuilder::new(),
+        name,
+        &game,
+        "txt",
+        Default::default(),
+    )
+}
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+enum TransformOp {
+    MirrorH,
+    MirrorV,
+    MirrorBoth,
+    Rotate90,
+}
+
+#[derive(Deserialize, Debug)]
+struct TransformParams {
+    op: TransformOp,
+}
+
+async fn transform(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let params = match req.query::<TransformParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut game = find!(kv, name);
+    game.board = match params.op {
+        TransformOp::MirrorH => game.board.mirror(Axis::Horizontal),
+        TransformOp::MirrorV => game.board.mirror(Axis::Vertical),
+        TransformOp::MirrorBoth => game.board.mirror(Axis::Both),
+        TransformOp::Rotate90 => game.board.rotate90(),
+    };
+    if let Err(e) = save(&kv, name, &game).await {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    console_log!("transform name={} op={:?}", name, params.op);
+
+    respond(
+        ResponseBuilder::new(),
+        name,
+        &game,
+        "txt",
+        Default::default(),
+    )
+}
+
+#[derive(Deserialize, Debug)]
+struct RunParams {
+    max: Option<usize>,
+}
+
+async fn run_to_terminal(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let params = match req.query::<RunParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let max = params.max.unwrap_or(MAX_RUN);
+    if max > MAX_RUN {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("max must be at most {}", MAX_RUN)
+        );
+    }
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut game = find!(kv, name);
+    game.keep_seed();
+    let past = HistoryEntry::from(&game);
+    let report = game.run_to_terminal(max);
+    if let Err(e) = save(&kv, name, &game).await {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+    let depth = config(&ctx.env, "HISTORY_DEPTH", HISTORY_DEPTH);
+    remember(&kv, name, past, game.expires_at, depth).await;
+
+    Response::from_json(&report)
+}
+
+#[derive(Deserialize, Debug)]
+struct StreamParams {
+    max: Option<usize>,
+    cycle_window: Option<usize>,
+}
+
+async fn run_stream(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let params = match req.query::<StreamParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let max = params.max.unwrap_or(MAX_STREAM);
+    if max > MAX_STREAM {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("max must be at most {}", MAX_STREAM)
+        );
+    }
+
+    let cycle_window = params.cycle_window.unwrap_or(100);
+    if !(1..=MAX_RUN).contains(&cycle_window) {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("cycle_window must be between 1 and {}", MAX_RUN)
+        );
+    }
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let game = find!(kv, name);
+    let mut cycles = CycleDetector::new(cycle_window);
+    cycles.observe(&game);
+
+    // one line per generation, produced as the client reads so nothing is
+    // buffered, ending with a run report at the cap, once the game is
+    // terminal or once it repeats a recent board
+    let lines = stream::unfold(Some((game, max, cycles)), |state| async move {
+        let (mut game, remaining, mut cycles) = state?;
+        if remaining == 0 || game.is_terminal() {
+            return Some((report_line(&game, None), None));
+        }
+
+        game.next();
+        let line = format!(
+            "{{\"gen\":{},\"pop\":{},\"delta\":{}}}\n",
+            game.generation,
+            game.board.population(),
+            game.delta
+        );
+
+        match cycles.observe(&game) {
+            Some(period) => {
+                let lines = report_line(&game, Some(period))
+                    .map(|report| [line.into_bytes(), report].concat());
+                Some((lines, None))
+            }
+            None => Some((Ok(line.into_bytes()), Some((game, remaining - 1, cycles)))),
+        }
+    });
+
+    ResponseBuilder::new()
+        .with_header(header::CONTENT_TYPE.as_str(), "application/x-ndjson")?
+        .from_stream(lines)
+}
+
+fn report_line(game: &Game, period: Option<usize>) -> Result<Vec<u8>> {
+    let mut line = serde_json::to_vec(&game.report(period))?;
+    line.push(b'\n');
+    Ok(line)
+}
+
+#[derive(Deserialize, Debug)]
+struct WatchParams {
+    interval: Option<u64>,
+    format: Option<String>,
+}
+
+/// A watched game is stepped by the one connection holding its lease, kept
+/// under its own key. KV has no locks, so like views this is best-effort: two
+/// connections opened at once can both take it.
+fn stepper_key(name: &str) -> String {
+    format!("stepper:{}", name)
+}
+
+async fn lease(kv: &kv::KvStore, name: &str, holder: &str) -> std::result::Result<(), kv::KvError> {
+    kv.put(&stepper_key(name), holder)?
+        .expiration_ttl(MIN_TTL)
+        .execute()
+        .await
+}
+
+async fn watch(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n.to_string(),
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let upgrade = req.headers().get(header::UPGRADE.as_str())?;
+    if !upgrade.is_some_and(|u| u.eq_ignore_ascii_case("websocket")) {
+        fail!(StatusCode::UPGRADE_REQUIRED, "expected a websocket upgrade");
+    }
+
+    let params = match req.query::<WatchParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let render_params = match req.query::<RenderParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    if let Err(e) = render_params.validate() {
+        fail!(StatusCode::BAD_REQUEST, e);
+    }
+
+    let interval = params.interval.unwrap_or(MIN_WATCH_INTERVAL);
+    if !(MIN_WATCH_INTERVAL..=MAX_WATCH_INTERVAL).contains(&interval) {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!(
+                "interval must be between {} and {}",
+                MIN_WATCH_INTERVAL, MAX_WATCH_INTERVAL
+            )
+        );
+    }
+    let format = RenderFormat::from_ext(params.format.as_deref().unwrap_or("txt"));
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let game = find!(kv, &name);
+
+    if let Ok(Some(_)) = kv.get(&stepper_key(&name)).text().await {
+        fail!(
+            StatusCode::CONFLICT,
+            format!("game '{}' is already being watched", name)
+        );
+    }
+    // the ray id is unique to this request
+    let holder = match req.headers().get("cf-ray")? {
+        Some(ray) => ray,
+        None => Date::now().as_millis().to_string(),
+    };
+    if let Err(e) = lease(&kv, &name, &holder).await {
+        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    }
+
+    let depth = config(&ctx.env, "HISTORY_DEPTH", HISTORY_DEPTH);
+    let pair = WebSocketPair::new()?;
+    pair.server.accept()?;
+
+    console_log!(
+        "watch name={} format={} interval={}",
+        name,
+        format.ext(),
+        interval
+    );
+
+    let server = pair.server;
+    wasm_bindgen_futures::spawn_local(async move {
+        let reason = stream_generations(
+            &server,
+            &kv,
+            &name,
+            &holder,
+            game,
+            Duration::from_millis(interval),
+            format,
+            render_params,
+            depth,
+        )
+        .await;
+        // the lease outlives a failed delete by at most its ttl
+        if let Ok(Some(h)) = kv.get(&stepper_key(&name)).text().await {
+            if h == holder {
+                let _ = kv.delete(&stepper_key(&name)).await;
+            }
+        }
+        console_log!("watch name={} closed={}", name, reason);
+        let _ = server.close(Some(1000), Some(reason));
+    });
+
+    Response::from_websocket(pair.client)
+}
+
+/// Sends the current generation, then steps and sends the next one every
+/// `interval` until the game is terminal or cycles, is gone, the lease is lost
+/// or the client disconnects, returning why it stopped. The game is reloaded before
+/// each step so changes made through other routes aren't overwritten.
+#[allow(clippy::too_many_arguments)]
+async fn stream_generations(
+    server: &WebSocket,
+    kv: &kv::KvStore,
+    name: &str,
+    holder: &str,
+    mut game: Game,
+    interval: Duration,
+    format: RenderFormat,
+    params: RenderParams,
+    depth: usize,
+) -> String {
+    let mut events = match server.events() {
+        Ok(events) => events,
+        Err(e) => return e.to_string(),
+    };
+    let mut leased_at = now();
+    let mut cycles = CycleDetector::new(PERIOD_LOOKBACK);
+
+    loop {
+        let frame = match render::render(&game, format, params.clone().into_options(name)) {
+            Ok(frame) => frame,
+            Err(e) => return e.to_string(),
+        };
+        // binary formats go out as binary messages
+        let sent = match String::from_utf8(frame) {
+            Ok(text) => server.send_with_str(text),
+            Err(e) => server.send_with_bytes(e.into_bytes()),
+        };
+        if let Err(e) = sent {
+            return e.to_string();
+        }
+        if let Some(reason) = stop_reason(&game, &mut cycles) {
+            return reason.to_string();
+        }
+
+        // messages from the client are ignored, only closing matters
+        let mut tick = std::pin::pin!(Delay::from(interval));
+        loop {
+            match future::select(tick.as_mut(), events.next()).await {
+                Either::Left(_) => break,
+                Either::Right((Some(Ok(WebsocketEvent::Message(_))), _)) => continue,
+                Either::Right(_) => return "disconnected".to_string(),
+            }
+        }
+
+        match kv.get(&stepper_key(name)).text().await {
+            Ok(Some(h)) if h == holder => {}
+            _ => return "lease lost".to_string(),
+        }
+        // refresh the lease well before it expires
+        if now() >= leased_at + MIN_TTL / 2 {
+            if let Err(e) = lease(kv, name, holder).await {
+                return e.to_string();
+            }
+            leased_at = now();
+        }
+
+        let last = game.generation;
+        game = match kv.get(name).json::<Game>().await {
+            Ok(Some(g)) if g.is_expired(now()) => return "expired".to_string(),
+            Ok(Some(g)) => g,
+            Ok(None) => return "gone".to_string(),
+            Err(e) => return e.to_string(),
+        };
+        // a game reset through another route starts cycling afresh
+        if game.generation < last {
+            cycles = CycleDetector::new(PERIOD_LOOKBACK);
+        }
+        game.keep_seed();
+        let past = HistoryEntry::from(&game);
+        game.next();
+        if let Err(e) = save(kv, name, &game).await {
+            return e.to_string();
         }
-    };
+        remember(kv, name, past, game.expires_at, depth).await;
+    }
+}
+
+/// Why a watched game stops on its own: it's terminal, or it repeats a board
+/// it had in the last [`PERIOD_LOOKBACK`] generations and would step forever.
+fn stop_reason(game: &Game, cycles: &mut CycleDetector) -> Option<&'static str> {
+    if game.is_terminal() {
+        return Some("terminal");
+    }
+    cycles.observe(game).map(|_| "cycle")
 }
 
 #[derive(Deserialize, Debug)]
-struct RenderParams {
-    next: Option<bool>,
-    alive: Option<char>,
-    dead: Option<char>,
-    separator: Option<char>,
-    cell_size: Option<usize>,
-    stroke_width: Option<usize>,
-    stroke_color: Option<String>,
-    fill_color: Option<String>,
+struct ActivityParams {
+    steps: Option<usize>,
+    window: Option<usize>,
 }
 
-impl From<RenderParams> for SVGOptions {
-    fn from(p: RenderParams) -> Self {
-        SVGOptions::new(p.cell_size, p.stroke_width, p.stroke_color, p.fill_color)
+async fn activity(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let params = match req.query::<ActivityParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let steps = params.steps.unwrap_or(50);
+    if steps > MAX_RUN {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("steps must be at most {}", MAX_RUN)
+        );
     }
-}
 
-impl From<RenderParams> for TextOptions {
-    fn from(p: RenderParams) -> Self {
-        TextOptions::new(p.alive, p.dead, p.separator)
+    let window = params.window.unwrap_or(5);
+    if window == 0 {
+        fail!(StatusCode::BAD_REQUEST, "window must be at least 1");
     }
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut game = find!(kv, name);
+    Response::from_json(&game.activity(steps, window))
+}
+
+#[derive(Deserialize, Debug)]
+struct StatsParams {
+    steps: Option<usize>,
 }
 
-async fn render(req: Request, ctx: RouteContext<()>) -> Result<Response> {
+async fn stats(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     let name = match ctx.param("name") {
         Some(n) => n,
         None => fail!(StatusCode::BAD_REQUEST, "name is required"),
     };
 
-    let ext = name.split('.').last().unwrap_or("txt");
-    let name = name.trim_end_matches(&format!(".{}", ext));
+    let params = match req.query::<StatsParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let steps = params.steps.unwrap_or(50);
+    if steps > MAX_RUN {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("steps must be at most {}", MAX_RUN)
+        );
+    }
 
     let kv = match ctx.env.kv(KV_NAMESPACE) {
         Ok(kv) => kv,
         Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
     };
 
-    let mut game = match kv.get(name).json::<Game>().await {
-        Ok(Some(g)) => g,
-        Ok(None) => fail!(
-            StatusCode::NOT_FOUND,
-            format!("game '{}' does not exist", name)
-        ),
-        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    let mut game = find!(kv, name);
+    let (series, terminal) = game.population_series(steps);
+
+    ResponseBuilder::new()
+        .with_header("x-life-terminal", &terminal.to_string())?
+        .from_json(&series)
+}
+
+#[derive(Deserialize, Debug)]
+struct ClassifyParams {
+    steps: Option<usize>,
+}
+
+async fn classify(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
     };
 
-    let params = match req.query::<RenderParams>() {
+    let params = match req.query::<ClassifyParams>() {
         Ok(p) => p,
         Err(e) => fail!(StatusCode::BAD_REQUEST, e),
     };
 
-    if params.next.unwrap_or(false) {
-        game.next();
-        if let Err(e) = kv.put(name, &game)?.execute().await {
-            fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
-        }
+    let steps = params.steps.unwrap_or(500);
+    if steps > MAX_RUN {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("steps must be at most {}", MAX_RUN)
+        );
     }
 
-    let headers = build_headers! {
-        header::ETAG => game.generation,
-        "x-life-generation" => game.generation,
-        "x-life-delta" => game.delta
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
     };
 
-    let res = ResponseBuilder::new().with_headers(headers.into());
+    let mut game = find!(kv, name);
+    Response::from_json(&game.classify(steps))
+}
 
-    match ext {
-        "svg" => {
-            let svg = match render::svg(&game, params.into()) {
-                Ok(svg) => svg,
-                Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
-            };
-            Ok(res
-                .with_header(header::CONTENT_TYPE.as_str(), "image/svg+xml")?
-                .fixed(svg.into()))
-        }
-        _ => {
-            let text = render::text(&game, params.into());
-            res.with_header(header::CONTENT_TYPE.as_str(), "text/plain; charset=utf-8")?
-                .ok(text)
-        }
-    }
+#[derive(Serialize, Debug)]
+struct Info<'a> {
+    name: &'a str,
+    generation: usize,
+    delta: usize,
+    rows: usize,
+    cols: usize,
+    population: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    views: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    created_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    expires_at: Option<u64>,
+}
+
+async fn info(_req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let name = match ctx.param("name") {
+        Some(n) => n,
+        None => fail!(StatusCode::BAD_REQUEST, "name is required"),
+    };
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let game = find!(kv, name);
+    let views = match config(&ctx.env, "VIEW_COUNTER", true) {
+        true => Some(views(&kv, name).await),
+        false => None,
+    };
+
+    Response::from_json(&Info {
+        name,
+        generation: game.generation,
+        delta: game.delta,
+        rows: game.board.rows(),
+        cols: game.board.cols(),
+        population: game.board.population(),
+        views,
+        created_at: game.created_at,
+        expires_at: game.expires_at,
+    })
 }
 
 #[derive(Deserialize, Debug)]
-struct CreatorParams {
-    alive: Option<char>,
-    dead: Option<char>,
-    separator: Option<char>,
+struct ViewParams {
+    interval: Option<u64>,
 }
 
-async fn create(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
+/// An auto-advancing page for sharing a game. The SVG options in the query
+/// are passed on to every frame.
+async fn view(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
     let name = match ctx.param("name") {
         Some(n) => n,
         None => fail!(StatusCode::BAD_REQUEST, "name is required"),
     };
 
-    if !name.chars().all(|c| c.is_alphanumeric() || c == '-') {
+    let interval = match req.query::<ViewParams>() {
+        Ok(p) => p.interval.unwrap_or(2_000),
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+    if !(MIN_WATCH_INTERVAL..=MAX_WATCH_INTERVAL).contains(&interval) {
         fail!(
             StatusCode::BAD_REQUEST,
-            "game name must be alphanumeric or '-'"
+            format!(
+                "interval must be between {} and {}",
+                MIN_WATCH_INTERVAL, MAX_WATCH_INTERVAL
+            )
         );
     }
 
-    let params = match req.query::<CreatorParams>() {
+    let params = match req.query::<RenderParams>() {
         Ok(p) => p,
         Err(e) => fail!(StatusCode::BAD_REQUEST, e),
     };
 
-    let body = match req.text().await {
-        Ok(b) => b,
+    if let Err(e) = params.validate() {
+        fail!(StatusCode::BAD_REQUEST, e);
+    }
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let game = find!(kv, name);
+
+    let svg_url = frame_url(req.url()?, name);
+    match render::viewer(&game, name, &svg_url, interval, params.into()) {
+        Ok(html) => Ok(ResponseBuilder::new()
+            .with_header(header::CONTENT_TYPE.as_str(), "text/html; charset=utf-8")?
+            .fixed(html.into_bytes())),
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct MontageParams {
+    names: String,
+    cols: Option<usize>,
+    format: Option<String>,
+    cell_size: Option<usize>,
+}
+
+async fn montage(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let params = match req.query::<MontageParams>() {
+        Ok(p) => p,
         Err(e) => fail!(StatusCode::BAD_REQUEST, e),
     };
 
-    let board = match Board::from_seed(body, params.alive, params.dead, params.separator) {
-        Ok(b) => b,
+    if let Err(e) = SVGOptions::validate(params.cell_size, None, None, None) {
+        fail!(StatusCode::BAD_REQUEST, e);
+    }
+
+    if let Some(format) = params.format.as_deref().filter(|f| *f != "svg") {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("unsupported montage format: '{}'", format)
+        );
+    }
+
+    let names = params.names.split(',').collect::<Vec<&str>>();
+    if names.len() > MAX_MONTAGE {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("at most {} games can be tiled", MAX_MONTAGE)
+        );
+    }
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut games = Vec::with_capacity(names.len());
+    for name in &names {
+        games.push(find!(kv, name));
+    }
+
+    // roughly square by default
+    let cols = params
+        .cols
+        .unwrap_or_else(|| (names.len() as f64).sqrt().ceil() as usize);
+    let tiles = names.iter().copied().zip(games.iter()).collect::<Vec<_>>();
+    let opts = SVGOptions::new(params.cell_size, None, None, None, None);
+
+    match render::montage(&tiles, cols, opts) {
+        Ok(svg) => Ok(ResponseBuilder::new()
+            .with_header(header::CONTENT_TYPE.as_str(), "image/svg+xml")?
+            .fixed(svg.into_bytes())),
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    }
+}
+
+#[derive(Deserialize, Debug)]
+struct CompareParams {
+    a: String,
+    b: String,
+}
+
+/// Whether two boards have the same cells. Boards of different sizes are
+/// never identical and have no count of differing cells, the error says why.
+#[derive(Serialize, Debug)]
+struct Comparison {
+    identical: bool,
+    differing: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+async fn compare(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let params = match req.query::<CompareParams>() {
+        Ok(p) => p,
         Err(e) => fail!(StatusCode::BAD_REQUEST, e),
     };
 
@@ -150,34 +2068,185 @@ async fn create(mut req: Request, ctx: RouteContext<()>) -> Result<Response> {
         Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
     };
 
-    let game_exists = match kv.get(name).text().await {
-        Ok(Some(_)) => true,
-        Ok(None) => false,
-        Err(_) => false,
+    let a = find!(kv, &params.a);
+    let b = find!(kv, &params.b);
+
+    let comparison = match a.board.hamming(&b.board) {
+        Some(differing) => Comparison {
+            identical: differing == 0,
+            differing: Some(differing),
+            error: None,
+        },
+        None => Comparison {
+            identical: false,
+            differing: None,
+            error: Some(format!(
+                "boards differ in size: '{}' is {}x{}, '{}' is {}x{}",
+                params.a,
+                a.board.rows(),
+                a.board.cols(),
+                params.b,
+                b.board.rows(),
+                b.board.cols()
+            )),
+        },
+    };
+
+    Response::from_json(&comparison)
+}
+
+/// URL-safe base64 with or without its `=` padding, both of which clients
+/// send.
+const URL_SAFE: GeneralPurpose = GeneralPurpose::new(
+    &alphabet::URL_SAFE,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+#[derive(Deserialize, Debug)]
+struct PreviewParams {
+    seed: String,
+    alive: Option<char>,
+    dead: Option<char>,
+    #[serde(default, deserialize_with = "separator")]
+    separator: Option<char>,
+    steps: Option<usize>,
+    format: Option<String>,
+}
+
+/// Renders a game seeded from URL-safe base64 in the query, for clients that
+/// can only GET. The game is stepped and rendered without being saved.
+async fn preview(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let params = match req.query::<PreviewParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+    let render_params = match req.query::<RenderParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
     };
+    if let Err(e) = render_params.validate() {
+        fail!(StatusCode::BAD_REQUEST, e);
+    }
 
-    if game_exists {
+    let steps = params.steps.unwrap_or(0);
+    let max_steps = config(&ctx.env, "MAX_STEPS", MAX_STEPS);
+    if steps > max_steps {
         fail!(
-            StatusCode::CONFLICT,
-            format!("game '{}' already exists", name)
+            StatusCode::BAD_REQUEST,
+            format!("steps must be at most {}", max_steps)
         );
     }
 
-    let game = Game::from(board);
-    if let Err(e) = kv.put(name, &game)?.execute().await {
-        fail!(StatusCode::INTERNAL_SERVER_ERROR, e);
+    let seed = match decode_seed(&params.seed) {
+        Ok(seed) => seed,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, format!("invalid seed: {}", e)),
+    };
+
+    let opts = SeedOptions::new(params.alive, params.dead, params.separator, None, None);
+    let opts = limit_seed(&ctx.env, opts);
+    let mut game = match Board::from_seed(seed, opts) {
+        Ok(board) => Game::from(board),
+        Err(e) => fail!(e.status_code(), e),
+    };
+    let report = game.run_until_terminal(steps);
+
+    let mut headers = render::metadata(&game);
+    headers.insert("x-life-steps", HeaderValue::from(report.generations));
+    let res = ResponseBuilder::new().with_headers(headers.into());
+    let format = params.format.unwrap_or_else(|| "txt".to_string());
+    respond(res, "preview", &game, &format, render_params)
+}
+
+/// A preview seed, URL-safe base64 of UTF-8 text so it fits in a query.
+fn decode_seed(seed: &str) -> std::result::Result<String, String> {
+    let seed = URL_SAFE.decode(seed.trim()).map_err(|e| e.to_string())?;
+    String::from_utf8(seed).map_err(|e| e.to_string())
+}
+
+#[derive(Deserialize, Debug)]
+struct ListParams {
+    limit: Option<usize>,
+    offset: Option<usize>,
+}
+
+#[derive(Serialize, Debug)]
+struct Listing {
+    name: String,
+    #[serde(flatten)]
+    summary: GameSummary,
+}
+
+/// Lists games by name, `limit` at a time starting at `offset`, with the
+/// total in `x-life-total`. KV only pages by cursor, so every key is listed
+/// to count and skip them.
+async fn list(req: Request, ctx: RouteContext<Context>) -> Result<Response> {
+    let params = match req.query::<ListParams>() {
+        Ok(p) => p,
+        Err(e) => fail!(StatusCode::BAD_REQUEST, e),
+    };
+
+    let limit = params.limit.unwrap_or(20);
+    if !(1..=MAX_LIST).contains(&limit) {
+        fail!(
+            StatusCode::BAD_REQUEST,
+            format!("limit must be between 1 and {}", MAX_LIST)
+        );
+    }
+    let offset = params.offset.unwrap_or(0);
+
+    let kv = match ctx.env.kv(KV_NAMESPACE) {
+        Ok(kv) => kv,
+        Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+    };
+
+    let mut keys = Vec::new();
+    let mut cursor = None;
+    loop {
+        let mut list = kv.list();
+        if let Some(cursor) = cursor {
+            list = list.cursor(cursor);
+        }
+        let page = match list.execute().await {
+            Ok(page) => page,
+            Err(e) => fail!(StatusCode::INTERNAL_SERVER_ERROR, e),
+        };
+        // game names can't contain ':', other keys like views do
+        keys.extend(page.keys.into_iter().filter(|k| !k.name.contains(':')));
+        if page.list_complete || page.cursor.is_none() {
+            break;
+        }
+        cursor = page.cursor;
+    }
+
+    let total = keys.len();
+    let mut games = Vec::new();
+    for key in keys.into_iter().skip(offset).take(limit) {
+        let summary = match key.metadata.map(serde_json::from_value) {
+            Some(Ok(summary)) => summary,
+            // games saved before summaries were kept
+            _ => match kv.get(&key.name).json::<Game>().await {
+                Ok(Some(game)) if !game.is_expired(now()) => GameSummary::from(&game),
+                // expired, deleted since it was listed or unreadable, one
+                // game shouldn't fail the whole listing
+                _ => continue,
+            },
+        };
+        games.push(Listing {
+            name: key.name,
+            summary,
+        });
     }
 
     ResponseBuilder::new()
-        .with_status(StatusCode::CREATED.into())
-        .ok(render::text(&game, Default::default()))
+        .with_header("x-life-total", &total.to_string())?
+        .from_json(&games)
 }
 
 #[event(fetch)]
-async fn main(req: Request, env: Env, _ctx: Context) -> Result<Response> {
+async fn main(req: Request, env: Env, ctx: Context) -> Result<Response> {
     console_error_panic_hook::set_once();
 
-    let mut response = Router::new()
+    let mut response = Router::with_data(ctx)
         .get("/", |_, _| {
             let url = "https://github.com/robherley/game-of-life".parse()?;
             Response::redirect(url)
@@ -186,8 +2255,29 @@ async fn main(req: Request, env: Env, _ctx: Context) -> Result<Response> {
             fail!(StatusCode::NOT_FOUND, "not found")
         })
         .get("/_ping", |_, _| Response::ok("pong"))
+        .get_async("/_montage", montage)
+        .get_async("/_games", list)
+        .post_async("/_batch", batch)
+        .get_async("/_preview", preview)
+        .get_async("/_diff", compare)
         .get_async("/:name", render)
         .post_async("/:name", create)
+        .delete_async("/:name", delete)
+        .post_async("/:name/fork", fork)
+        .post_async("/:name/reset", reset)
+        .post_async("/:name/crop", crop)
+        .post_async("/:name/transform", transform)
+        .post_async("/:name/rewind", rewind)
+        .post_async("/:name/random", random)
+        .post_async("/:name/run_to_terminal", run_to_terminal)
+        .get_async("/:name/run_stream", run_stream)
+        .get_async("/:name/ws", watch)
+        .get_async("/:name/activity", activity)
+        .get_async("/:name/stats", stats)
+        .get_async("/:name/available", available)
+        .get_async("/:name/info", info)
+        .get_async("/:name/view", view)
+        .get_async("/:name/classify", classify)
         .run(req, env)
         .await?;
 
@@ -203,3 +2293,226 @@ async fn main(req: Request, env: Env, _ctx: Context) -> Result<Response> {
 
     Ok(response)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn limits_name_length() {
+        let name = "a".repeat(MAX_NAME_LENGTH);
+        assert!(validate_name(&name, MAX_NAME_LENGTH).is_ok());
+        assert!(validate_name(&format!("{}a", name), MAX_NAME_LENGTH).is_err());
+        // counted in characters, not bytes
+        assert!(validate_name(&"é".repeat(MAX_NAME_LENGTH), MAX_NAME_LENGTH).is_ok());
+        assert!(validate_name("a-b", 2).is_err());
+        assert!(validate_name("a_b", 10).is_err());
+    }
+
+    #[test]
+    fn parses_named_separators() {
+        assert_eq!(parse_separator("newline"), Ok('\n'));
+        assert_eq!(parse_separator("pipe"), Ok('|'));
+        assert_eq!(parse_separator(";"), Ok(';'));
+        assert!(parse_separator("ab").is_err());
+    }
+
+    fn game(seed: &str) -> Game {
+        Game::from(Board::from_seed(seed.to_string(), SeedOptions::default()).unwrap())
+    }
+
+    /// Steps a watched game like [`stream_generations`] does, returning the
+    /// text frame of every generation sent and why it stopped.
+    fn watch(mut game: Game, max: usize) -> (Vec<String>, Option<&'static str>) {
+        let mut cycles = CycleDetector::new(PERIOD_LOOKBACK);
+        let mut frames = Vec::new();
+        for _ in 0..max {
+            frames.push(render::text(&game, TextOptions::new(None, None, None)));
+            if let Some(reason) = stop_reason(&game, &mut cycles) {
+                return (frames, Some(reason));
+            }
+            game.next();
+        }
+        (frames, None)
+    }
+
+    #[test]
+    fn watching_sends_distinct_generations() {
+        let glider = ".#....\n..#...\n###...\n......\n......\n......";
+        let (frames, reason) = watch(game(glider), 3);
+        assert_eq!(reason, None);
+        assert_ne!(frames[0], frames[1]);
+        assert_ne!(frames[1], frames[2]);
+    }
+
+    #[test]
+    fn watching_stops_at_cycles_and_terminal_games() {
+        let (frames, reason) = watch(game(".....\n..#..\n..#..\n..#..\n....."), 10);
+        assert_eq!(reason, Some("cycle"));
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0], frames[2]);
+
+        let (frames, reason) = watch(game("##\n##"), 10);
+        assert_eq!(reason, Some("terminal"));
+        assert_eq!(frames.len(), 2);
+    }
+
+    fn url(url: &str) -> Url {
+        Url::parse(url).unwrap()
+    }
+
+    #[test]
+    fn etags_are_quoted_and_start_with_the_generation() {
+        let mut glider = game(".#.\n..#\n###");
+        let tag = etag(&glider, &url("https://life.test/glider.svg"), "svg");
+        assert!(tag.starts_with("\"0-") && tag.ends_with('"'), "{tag}");
+        assert_eq!(tag.matches('"').count(), 2);
+
+        // the format, options and board all change the tag
+        assert_ne!(
+            tag,
+            etag(&glider, &url("https://life.test/glider.svg"), "txt")
+        );
+        assert_ne!(
+            tag,
+            etag(
+                &glider,
+                &url("https://life.test/glider.svg?cell_size=5"),
+                "svg"
+            )
+        );
+        glider.next();
+        let stepped = etag(&glider, &url("https://life.test/glider.svg"), "svg");
+        assert!(stepped.starts_with("\"1-"), "{stepped}");
+    }
+
+    #[test]
+    fn matches_strong_weak_and_listed_etags() {
+        let tag = "\"3-abc\"";
+        assert!(matches_etag("\"3-abc\"", tag));
+        assert!(matches_etag("W/\"3-abc\"", tag));
+        assert!(matches_etag("*", tag));
+        assert!(matches_etag("\"1-def\", W/\"3-abc\"", tag));
+        assert!(matches_etag("\"1-def\",\"3-abc\"", tag));
+        assert!(!matches_etag("\"1-def\"", tag));
+        // unquoted tags aren't the same tag
+        assert!(!matches_etag("3-abc", tag));
+        assert!(!matches_etag("", tag));
+    }
+
+    #[test]
+    fn rerequesting_with_the_etag_is_not_modified() {
+        let mut glider = game(".#.\n..#\n###");
+        let glider_url = url("https://life.test/glider.svg");
+        let first = etag(&glider, &glider_url, "svg");
+
+        // a plain render of the same generation is served from the client's copy
+        let again = etag(&glider, &glider_url, "svg");
+        assert!(is_not_modified(false, Some(&first), &again));
+        assert!(!is_not_modified(false, None, &again));
+
+        // ?next=true always renders the new generation
+        assert!(!is_not_modified(true, Some(&first), &again));
+        glider.next();
+        let next = etag(&glider, &glider_url, "svg");
+        assert!(!is_not_modified(false, Some(&first), &next));
+    }
+
+    #[test]
+    fn decodes_url_safe_seeds() {
+        assert_eq!(decode_seed(" IyMKIz8= ").unwrap(), "##\n#?");
+        // '/' in standard base64 is '_' in the URL-safe one
+        assert_eq!(decode_seed("Pj4_").unwrap(), ">>?");
+        assert!(decode_seed("Pj4/").is_err());
+        // valid base64 of a lone 0xff byte, which isn't UTF-8
+        assert!(decode_seed("_w==").is_err());
+    }
+
+    #[test]
+    fn limits_dimensions_and_seeds() {
+        let limits = || SeedOptions {
+            max_rows: 3,
+            max_cols: 4,
+            ..Default::default()
+        };
+        assert!(check_dimensions(&limits(), 3, 4).is_ok());
+        for (rows, cols) in [(4, 4), (3, 5)] {
+            assert!(matches!(
+                check_dimensions(&limits(), rows, cols),
+                Err(BoardError::DimensionsTooLarge {
+                    max_rows: 3,
+                    max_cols: 4,
+                    ..
+                })
+            ));
+        }
+
+        let seed = |rows: usize, cols: usize| vec!["#".repeat(cols); rows].join("\n");
+        assert!(Board::from_seed(seed(3, 4), limits()).is_ok());
+        for (rows, cols) in [(4, 4), (3, 5)] {
+            assert!(matches!(
+                Board::from_seed(seed(rows, cols), limits()),
+                Err(BoardError::DimensionsTooLarge { .. })
+            ));
+        }
+    }
+
+    #[test]
+    fn rewinds_through_the_history() {
+        let mut game = game(".....\n..#..\n..#..\n..#..\n.....");
+        game.board.boundary = Boundary::Toroidal;
+        let start = game.clone();
+
+        let mut history = Vec::new();
+        for _ in 0..5 {
+            let past = serde_json::to_string(&HistoryEntry::from(&game)).unwrap();
+            push_history(&mut history, serde_json::from_str(&past).unwrap(), 3);
+            game.next();
+        }
+        let kept = history.iter().map(|e| e.generation).collect::<Vec<_>>();
+        assert_eq!(kept, [2, 3, 4]);
+
+        let past = history.remove(0);
+        past.restore(&mut game).unwrap();
+        assert_eq!(game.generation, 2);
+        assert_eq!(game.delta, 4);
+        assert_eq!(game.board.grid, start.board.grid);
+        assert_eq!(game.board.boundary, Boundary::Toroidal);
+
+        // remembering an earlier generation drops the rewound timeline
+        push_history(&mut history, HistoryEntry::from(&game), 3);
+        let kept = history.iter().map(|e| e.generation).collect::<Vec<_>>();
+        assert_eq!(kept, [2]);
+    }
+
+    #[test]
+    fn picks_content_encodings() {
+        assert_eq!(content_encoding("gzip, deflate"), Some("gzip"));
+        assert_eq!(content_encoding("gzip, br"), Some("br"));
+        assert_eq!(content_encoding("br;q=0.5, gzip"), Some("gzip"));
+        assert_eq!(content_encoding("gzip;q=0, br;q=0"), None);
+        assert_eq!(content_encoding("identity"), None);
+        assert_eq!(content_encoding("*"), None);
+
+        for ext in ["gif", "png"] {
+            assert!(!RenderFormat::from_ext(ext).is_compressible(), "{ext}");
+        }
+        for ext in ["txt", "svg", "json", "npy"] {
+            assert!(RenderFormat::from_ext(ext).is_compressible(), "{ext}");
+        }
+    }
+
+    #[test]
+    fn viewers_fetch_stepped_frames() {
+        let url =
+            url("https://life.test/fig8/view?interval=500&cell_size=5&steps=3&fill_color=red");
+        let frame = frame_url(url, "fig8");
+        assert_eq!(frame, "/fig8.svg?cell_size=5&fill_color=red&next=true");
+
+        let html = render::viewer(&game("#"), "fig8", &frame, 500, Default::default()).unwrap();
+        assert!(html.contains("<title>fig8</title>"));
+        assert!(html.contains(
+            r#"data-src="/fig8.svg?cell_size=5&amp;fill_color=red&amp;next=true" data-interval="500""#
+        ));
+    }
+}
@@ -1,9 +1,263 @@
-use crate::game::{Game, ALIVE, DEAD, SEPARATOR};
+use crate::game::{Board, Game, Rule, ALIVE, DEAD, SEPARATOR};
+use http::{header, HeaderMap, HeaderValue, StatusCode};
 use quick_xml::{
+    escape::escape,
     events::{BytesEnd, BytesStart, BytesText, Event},
     writer::Writer,
 };
-use serde::Deserialize;
+use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
+use std::{borrow::Cow, io::Write};
+use thiserror::Error;
+use xxhash_rust::xxh64::xxh64;
+
+pub const MAX_CELL_SIZE: usize = 100;
+pub const MAX_PRECISION: usize = 6;
+pub const MAX_FONT_SIZE: usize = 72;
+pub const MAX_FRAMES: usize = 500;
+
+const BAND_HEIGHT: f64 = 20.0;
+const MONTAGE_GAP: f64 = 10.0;
+const RULER_TICK: f64 = 4.0;
+// longest line of an rle body, as recommended by the format
+const RLE_LINE_LENGTH: usize = 70;
+// advance of a 12px monospace digit, rounded up, scaled with the font size
+const RULER_CHAR_WIDTH: f64 = 8.0;
+const FONT_SIZE: usize = 12;
+// pixels in a single raster frame, a 2048x2048 image
+const MAX_IMAGE_PIXELS: usize = 1 << 22;
+// pixels of all the frames of a GIF together, 16 frames of 2048x2048
+const MAX_GIF_PIXELS: usize = 1 << 26;
+// cells born and cells that died in a diff
+const DIFF_BORN: char = '+';
+const DIFF_DIED: char = '-';
+// age at which a cell reaches the old end of an age gradient
+const AGE_RAMP: u32 = 32;
+
+#[derive(Debug, Error)]
+pub enum GifError {
+    #[error("invalid color: '{0}'")]
+    InvalidColor(String),
+    #[error(
+        "image of {width}x{height} is too large, expected at most {max} pixels and 65535 per side"
+    )]
+    TooLarge {
+        width: usize,
+        height: usize,
+        max: usize,
+    },
+    #[error(
+        "{frames} frames of {width}x{height} are too large, expected at most {max} pixels in total"
+    )]
+    TooManyFrames {
+        frames: usize,
+        width: usize,
+        height: usize,
+        max: usize,
+    },
+    #[error(transparent)]
+    Encoding(#[from] gif::EncodingError),
+}
+
+impl GifError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            GifError::InvalidColor(_) => StatusCode::BAD_REQUEST,
+            GifError::TooLarge { .. } | GifError::TooManyFrames { .. } => {
+                StatusCode::PAYLOAD_TOO_LARGE
+            }
+            GifError::Encoding(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum PngError {
+    #[error("invalid color: '{0}'")]
+    InvalidColor(String),
+    #[error("image of {width}x{height} is too large, expected at most {max} pixels")]
+    TooLarge {
+        width: usize,
+        height: usize,
+        max: usize,
+    },
+    #[error(transparent)]
+    Encoding(#[from] png::EncodingError),
+}
+
+impl PngError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            PngError::InvalidColor(_) => StatusCode::BAD_REQUEST,
+            PngError::TooLarge { .. } => StatusCode::PAYLOAD_TOO_LARGE,
+            PngError::Encoding(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// Render options out of bounds, rejected rather than rendering something
+/// invisible or huge.
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum OptionsError {
+    #[error("cell_size must be between 1 and {max}, got {value}")]
+    CellSize { value: usize, max: usize },
+    #[error("stroke_width must be at most cell_size ({cell_size}), got {value}")]
+    StrokeWidth { value: usize, cell_size: usize },
+    #[error("precision must be at most {max}, got {value}")]
+    Precision { value: usize, max: usize },
+    #[error("font_size must be between 1 and {max}, got {value}")]
+    FontSize { value: usize, max: usize },
+}
+
+#[derive(Debug, Error)]
+pub enum RenderError {
+    #[error(transparent)]
+    Svg(#[from] quick_xml::Error),
+    #[error(transparent)]
+    Gif(#[from] GifError),
+    #[error(transparent)]
+    Png(#[from] PngError),
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+impl RenderError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            RenderError::Svg(_) | RenderError::Json(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            RenderError::Gif(e) => e.status_code(),
+            RenderError::Png(e) => e.status_code(),
+        }
+    }
+}
+
+/// Output formats, picked by the extension of the requested name.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RenderFormat {
+    Text,
+    Svg,
+    AnimatedSvg,
+    Html,
+    Npy,
+    Rle,
+    Gif,
+    Png,
+    Json,
+    Life106,
+    Ansi,
+    Cells,
+    Diff,
+}
+
+impl RenderFormat {
+    /// Maps an extension to its format, falling back to text.
+    pub fn from_ext(ext: &str) -> Self {
+        match ext {
+            "svg" => Self::Svg,
+            "anim.svg" => Self::AnimatedSvg,
+            "html" => Self::Html,
+            "npy" => Self::Npy,
+            "rle" => Self::Rle,
+            "gif" => Self::Gif,
+            "png" => Self::Png,
+            "json" => Self::Json,
+            "lif" | "life" => Self::Life106,
+            "ansi" => Self::Ansi,
+            "cells" => Self::Cells,
+            "diff" => Self::Diff,
+            _ => Self::Text,
+        }
+    }
+
+    /// Picks the format for a request: the extension if there is one,
+    /// otherwise the most preferred of SVG, JSON and text in an `Accept`
+    /// header, falling back to text.
+    pub fn negotiate(accept: Option<&str>, ext: Option<&str>) -> Self {
+        if let Some(ext) = ext {
+            return Self::from_ext(ext);
+        }
+
+        let mut ranges = accept
+            .unwrap_or_default()
+            .split(',')
+            .filter_map(|range| {
+                let mut parts = range.split(';').map(str::trim);
+                let media = parts.next()?;
+                let q = parts
+                    .find_map(|p| p.strip_prefix("q="))
+                    .map_or(Some(1.0), |q| q.parse::<f32>().ok())?;
+                Some((media, q))
+            })
+            .filter(|(_, q)| *q > 0.0)
+            .collect::<Vec<_>>();
+        // stable, so equally preferred types keep the client's order
+        ranges.sort_by(|(_, a), (_, b)| b.total_cmp(a));
+
+        ranges
+            .into_iter()
+            .find_map(|(media, _)| match media {
+                "image/svg+xml" => Some(Self::Svg),
+                "application/json" => Some(Self::Json),
+                "text/plain" => Some(Self::Text),
+                _ => None,
+            })
+            .unwrap_or(Self::Text)
+    }
+
+    /// The extension that renders this format.
+    pub fn ext(&self) -> &'static str {
+        match self {
+            Self::Text => "txt",
+            Self::Svg => "svg",
+            Self::AnimatedSvg => "anim.svg",
+            Self::Html => "html",
+            Self::Npy => "npy",
+            Self::Rle => "rle",
+            Self::Gif => "gif",
+            Self::Png => "png",
+            Self::Json => "json",
+            Self::Life106 => "lif",
+            Self::Ansi => "ansi",
+            Self::Cells => "cells",
+            Self::Diff => "diff",
+        }
+    }
+
+    /// Whether bodies of this format shrink when compressed, unlike the
+    /// already compressed images.
+    pub fn is_compressible(&self) -> bool {
+        !matches!(self, Self::Gif | Self::Png)
+    }
+
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            Self::Text | Self::Rle | Self::Life106 | Self::Ansi | Self::Cells | Self::Diff => {
+                "text/plain; charset=utf-8"
+            }
+            Self::Svg | Self::AnimatedSvg => "image/svg+xml",
+            Self::Html => "text/html; charset=utf-8",
+            Self::Npy => "application/octet-stream",
+            Self::Gif => "image/gif",
+            Self::Png => "image/png",
+            Self::Json => "application/json",
+        }
+    }
+}
+
+/// Options for every format, only the ones for the rendered format are used.
+/// `name` titles the HTML page.
+#[derive(Default)]
+pub struct RenderOptions {
+    pub name: String,
+    /// Render an [`activity_map`] over this many generations instead (SVG).
+    pub activity: Option<usize>,
+    /// Generations in an animation, 20 by default.
+    pub frames: Option<usize>,
+    /// Milliseconds per generation in a GIF, 100 by default.
+    pub delay: Option<usize>,
+    pub text: TextOptions,
+    pub ansi: AnsiOptions,
+    pub svg: SVGOptions,
+}
 
 #[derive(Deserialize, Debug)]
 pub struct TextOptions {
@@ -36,7 +290,7 @@ pub fn text(game: &Game, opts: TextOptions) -> String {
         for cell in row {
             result.push(if *cell { opts.alive } else { opts.dead });
         }
-        if i < board.rows() - 1 {
+        if i + 1 < board.rows() {
             result.push(opts.separator);
         }
     }
@@ -44,79 +298,1446 @@ pub fn text(game: &Game, opts: TextOptions) -> String {
     result
 }
 
+/// The step from the current generation to the next, as text where born
+/// cells are `+`, cells that died are `-`, and the rest are drawn as usual.
+/// Nothing is saved.
+pub fn diff(game: &Game, opts: TextOptions) -> String {
+    let mut board = game.board.clone();
+    let delta = board.next_delta();
+
+    let mut cells = board
+        .grid
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|cell| if *cell { opts.alive } else { opts.dead })
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    for &(row, col) in &delta.born {
+        cells[row][col] = DIFF_BORN;
+    }
+    for &(row, col) in &delta.died {
+        cells[row][col] = DIFF_DIED;
+    }
+
+    cells
+        .into_iter()
+        .map(String::from_iter)
+        .collect::<Vec<_>>()
+        .join(&opts.separator.to_string())
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct AnsiOptions {
+    /// RGB of the live cells, white by default.
+    pub fg: [u8; 3],
+    /// RGB behind the board, black by default.
+    pub bg: [u8; 3],
+    /// Leave out the escape sequences, only drawing the blocks.
+    pub plain: bool,
+}
+
+impl AnsiOptions {
+    pub fn new(fg: Option<[u8; 3]>, bg: Option<[u8; 3]>, plain: Option<bool>) -> Self {
+        Self {
+            fg: fg.unwrap_or([255, 255, 255]),
+            bg: bg.unwrap_or([0, 0, 0]),
+            plain: plain.unwrap_or(false),
+        }
+    }
+}
+
+impl Default for AnsiOptions {
+    fn default() -> Self {
+        Self::new(None, None, None)
+    }
+}
+
+/// Draws live cells as blocks and dead cells as spaces for terminals, two
+/// columns per cell so they come out roughly square. Each row sets 24-bit
+/// foreground and background colors and resets them at its end.
+pub fn ansi(game: &Game, opts: AnsiOptions) -> String {
+    let board = &game.board;
+    let ([fr, fg, fb], [br, bg, bb]) = (opts.fg, opts.bg);
+    let color = format!("\x1b[38;2;{};{};{};48;2;{};{};{}m", fr, fg, fb, br, bg, bb);
+    let reset = "\x1b[0m";
+
+    let mut result = String::new();
+    for cells in &board.grid {
+        if !opts.plain {
+            result.push_str(&color);
+        }
+        for cell in cells {
+            result.push_str(if *cell { "██" } else { "  " });
+        }
+        if !opts.plain {
+            result.push_str(reset);
+        }
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Encodes the grid as a NumPy `.npy` (format v1.0) array of `uint8` 0/1
+/// values with shape `(rows, cols)`, readable with `numpy.load`.
+pub fn npy(game: &Game) -> Vec<u8> {
+    let board = &game.board;
+    let mut header = format!(
+        "{{'descr': '|u1', 'fortran_order': False, 'shape': ({}, {}), }}",
+        board.rows(),
+        board.cols()
+    );
+
+    // magic, version and header length take 10 bytes, the header is padded
+    // with spaces so the data starts on a 64 byte boundary
+    let unpadded = 10 + header.len() + 1;
+    header.push_str(&" ".repeat((64 - unpadded % 64) % 64));
+    header.push('\n');
+
+    let mut result = Vec::with_capacity(10 + header.len() + board.rows() * board.cols());
+    result.extend_from_slice(b"\x93NUMPY\x01\x00");
+    result.extend_from_slice(&(header.len() as u16).to_le_bytes());
+    result.extend_from_slice(header.as_bytes());
+    result.extend(board.grid.iter().flatten().map(|cell| *cell as u8));
+
+    result
+}
+
+/// Encodes the board as RLE with the board's rule. Dead cells at the end of
+/// a row are dropped and runs of row ends are merged, as Golly writes them.
+pub fn rle(game: &Game) -> String {
+    let board = &game.board;
+    let mut tokens = Vec::new();
+    let token = |count: usize, tag: char| match count {
+        1 => tag.to_string(),
+        _ => format!("{}{}", count, tag),
+    };
+
+    let mut row_ends = 0;
+    for cells in &board.grid {
+        let mut runs: Vec<(bool, usize)> = Vec::new();
+        for cell in cells {
+            match runs.last_mut() {
+                Some((alive, count)) if alive == cell => *count += 1,
+                _ => runs.push((*cell, 1)),
+            }
+        }
+        if runs.last().is_some_and(|(alive, _)| !alive) {
+            runs.pop();
+        }
+
+        if !runs.is_empty() {
+            if row_ends > 0 {
+                tokens.push(token(row_ends, '$'));
+                row_ends = 0;
+            }
+            for (alive, count) in runs {
+                tokens.push(token(count, if alive { 'o' } else { 'b' }));
+            }
+        }
+        row_ends += 1;
+    }
+    tokens.push("!".to_string());
+
+    let mut result = format!(
+        "x = {}, y = {}, rule = {}\n",
+        board.cols(),
+        board.rows(),
+        board.rule
+    );
+    let mut line = 0;
+    for token in tokens {
+        if line + token.len() > RLE_LINE_LENGTH {
+            result.push('\n');
+            line = 0;
+        }
+        line += token.len();
+        result.push_str(&token);
+    }
+    result.push('\n');
+
+    result
+}
+
+/// Lists the `x y` (column and row) of every live cell under a `#Life 1.06`
+/// header, row by row.
+pub fn life106(game: &Game) -> String {
+    let mut result = "#Life 1.06\n".to_string();
+    for (row, cells) in game.board.grid.iter().enumerate() {
+        for (col, _) in cells.iter().enumerate().filter(|(_, alive)| **alive) {
+            result.push_str(&format!("{} {}\n", col, row));
+        }
+    }
+
+    result
+}
+
+/// The board as a plaintext (`.cells`) pattern of `.` and `O` rows, under
+/// `!` comments naming the game and its generation.
+pub fn cells(game: &Game, name: &str) -> String {
+    let mut result = format!("!Name: {}\n!Generation: {}\n", name, game.generation);
+    result.push_str(&game.board.stringify(Some('O'), Some('.'), None));
+    result.push('\n');
+
+    result
+}
+
+/// A game as clients see it, kept apart from the stored layout of [`Game`]
+/// so that can change without breaking them. The grid is rows of booleans.
+pub struct GameState<'a>(pub &'a Game);
+
+impl Serialize for GameState<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let game = self.0;
+        let mut state = serializer.serialize_struct("GameState", 7)?;
+        state.serialize_field("generation", &game.generation)?;
+        state.serialize_field("delta", &game.delta)?;
+        state.serialize_field("rows", &game.board.rows())?;
+        state.serialize_field("cols", &game.board.cols())?;
+        state.serialize_field("grid", &game.board.grid)?;
+        state.serialize_field("rule", &game.board.rule)?;
+        state.serialize_field("terminal", &game.is_terminal())?;
+        state.end()
+    }
+}
+
+/// The `(young, old)` colors of an age gradient.
+pub type Gradient = ([u8; 3], [u8; 3]);
+
+#[derive(Clone)]
 pub struct SVGOptions {
     pub cell_size: usize,
     pub stroke_width: usize,
     pub stroke_color: String,
     pub fill_color: String,
+    pub precision: usize,
+    pub title: Option<String>,
+    /// Interval of the ticks drawn along the top and left edges, if any.
+    pub ruler: Option<usize>,
+    /// Milliseconds each generation shows for in an animation.
+    pub frame_duration: usize,
+    /// Show the generation in a band below the board.
+    pub caption: bool,
+    /// Text of the caption of [`svg`], with `{gen}`, `{delta}`, `{rows}`,
+    /// `{cols}`, `{pop}` and `{rule}` filled in. An empty template hides the
+    /// caption.
+    pub caption_template: Option<String>,
+    pub font_family: String,
+    pub font_size: usize,
+    /// Colors live cells from the first color when born to the second once
+    /// they are [`AGE_RAMP`] generations old, instead of the fill color.
+    pub age_gradient: Option<Gradient>,
 }
 
 impl SVGOptions {
+    /// Checks requested sizes before they're clamped by [`SVGOptions::new`]:
+    /// cells must be `1..=MAX_CELL_SIZE`, strokes at most a cell wide,
+    /// precision at most [`MAX_PRECISION`] and fonts `1..=MAX_FONT_SIZE`.
+    pub fn validate(
+        cell_size: Option<usize>,
+        stroke_width: Option<usize>,
+        precision: Option<usize>,
+        font_size: Option<usize>,
+    ) -> Result<(), OptionsError> {
+        let cell_size = cell_size.unwrap_or(20);
+        if !(1..=MAX_CELL_SIZE).contains(&cell_size) {
+            return Err(OptionsError::CellSize {
+                value: cell_size,
+                max: MAX_CELL_SIZE,
+            });
+        }
+        if let Some(value) = stroke_width.filter(|w| *w > cell_size) {
+            return Err(OptionsError::StrokeWidth { value, cell_size });
+        }
+        if let Some(value) = precision.filter(|p| *p > MAX_PRECISION) {
+            return Err(OptionsError::Precision {
+                value,
+                max: MAX_PRECISION,
+            });
+        }
+        if let Some(value) = font_size.filter(|s| !(1..=MAX_FONT_SIZE).contains(s)) {
+            return Err(OptionsError::FontSize {
+                value,
+                max: MAX_FONT_SIZE,
+            });
+        }
+
+        Ok(())
+    }
+
     pub fn new(
         cell_size: Option<usize>,
         stroke_width: Option<usize>,
         stroke_color: Option<String>,
         fill_color: Option<String>,
+        precision: Option<usize>,
     ) -> Self {
+        // clamp degenerate sizes: zero-sized cells are invisible and huge ones
+        // blow up the output, strokes wider than a cell swallow the board.
+        // requests are validated first, this covers everything else
+        let cell_size = cell_size.unwrap_or(20).clamp(1, MAX_CELL_SIZE);
+
         Self {
-            cell_size: cell_size.unwrap_or(20),
-            stroke_width: stroke_width.unwrap_or(2),
+            cell_size,
+            stroke_width: stroke_width.unwrap_or(2).min(cell_size),
             stroke_color: stroke_color.unwrap_or("white".to_string()),
             fill_color: fill_color.unwrap_or("black".to_string()),
+            precision: precision.unwrap_or(2).min(MAX_PRECISION),
+            title: None,
+            ruler: None,
+            frame_duration: 250,
+            caption: true,
+            caption_template: None,
+            font_family: "monospace".to_string(),
+            font_size: FONT_SIZE,
+            age_gradient: None,
+        }
+    }
+
+    /// Whether the caption band is drawn.
+    pub fn shows_caption(&self) -> bool {
+        self.caption && self.caption_template.as_deref() != Some("")
+    }
+
+    /// Height of the title, ruler and caption bands, grown to fit larger
+    /// fonts.
+    fn band_height(&self) -> f64 {
+        BAND_HEIGHT.max(self.font_size as f64 + 8.0)
+    }
+
+    /// Formats a coordinate or length with at most `precision` decimals,
+    /// dropping trailing zeros so whole numbers stay short.
+    pub fn coord(&self, value: f64) -> String {
+        let s = format!("{:.*}", self.precision, value);
+        if s.contains('.') {
+            s.trim_end_matches('0').trim_end_matches('.').to_string()
+        } else {
+            s
         }
     }
 }
 
 impl Default for SVGOptions {
     fn default() -> Self {
-        Self::new(None, None, None, None)
+        Self::new(None, None, None, None, None)
     }
 }
 
+type SVGWriter = Writer<std::io::Cursor<Vec<u8>>>;
+
+/// Writes a line of text anchored (`start`, `middle` or `end`) at `x` whose
+/// baseline sits at `y`.
+fn write_text(
+    w: &mut SVGWriter,
+    (x, y): (&str, f64),
+    anchor: &str,
+    text: &str,
+    opts: &SVGOptions,
+) -> Result<(), quick_xml::Error> {
+    w.write_event(Event::Start(BytesStart::new("text").with_attributes(vec![
+        ("x", x),
+        ("y", &*opts.coord(y)),
+        ("font-family", &*opts.font_family),
+        ("font-size", &*opts.font_size.to_string()),
+        ("fill", &opts.fill_color),
+        ("dominant-baseline", "center"),
+        ("text-anchor", anchor),
+    ])))?;
+    w.write_event(Event::Text(BytesText::new(text)))?;
+    w.write_event(Event::End(BytesEnd::new("text")))?;
+    Ok(())
+}
+
+/// Draws a tick and index label every `step` columns above and rows left of
+/// the board, whose top left corner is at `origin`.
+fn write_ruler(
+    w: &mut SVGWriter,
+    board: &Board,
+    origin: (f64, f64),
+    step: usize,
+    opts: &SVGOptions,
+) -> Result<(), quick_xml::Error> {
+    let (left, top) = origin;
+    let cell_size = opts.cell_size as f64;
+    let mut tick = |x1: f64, y1: f64, x2: f64, y2: f64| {
+        w.write_event(Event::Empty(BytesStart::new("line").with_attributes(vec![
+            ("x1", &*opts.coord(x1)),
+            ("y1", &*opts.coord(y1)),
+            ("x2", &*opts.coord(x2)),
+            ("y2", &*opts.coord(y2)),
+            ("stroke", &*opts.fill_color),
+        ])))
+        .map(|_| ())
+    };
+
+    for col in (0..board.cols()).step_by(step) {
+        let x = left + (col as f64 + 0.5) * cell_size;
+        tick(x, top - RULER_TICK, x, top)?;
+    }
+    for row in (0..board.rows()).step_by(step) {
+        let y = top + (row as f64 + 0.5) * cell_size;
+        tick(left - RULER_TICK, y, left, y)?;
+    }
+
+    for col in (0..board.cols()).step_by(step) {
+        let x = opts.coord(left + (col as f64 + 0.5) * cell_size);
+        let y = top - RULER_TICK - opts.band_height() / 2.0 + 2.0;
+        write_text(w, (&x, y), "middle", &col.to_string(), opts)?;
+    }
+    for row in (0..board.rows()).step_by(step) {
+        let x = opts.coord(left - RULER_TICK - 2.0);
+        let y = top + (row as f64 + 0.5) * cell_size;
+        write_text(w, (&x, y), "end", &row.to_string(), opts)?;
+    }
+
+    Ok(())
+}
+
 pub fn svg(game: &Game, opts: SVGOptions) -> Result<String, quick_xml::Error> {
+    let caption = match &opts.caption_template {
+        Some(template) => caption(template, game),
+        None => {
+            let mut caption = format!("t = {}, Δ = {}", game.generation, game.delta);
+            // conway is assumed, other rules are named so the image says
+            // what it is
+            if game.board.rule != Rule::default() {
+                caption.push_str(&format!(", {}", game.board.rule));
+            }
+            caption
+        }
+    };
     let board = &game.board;
-    let width = board.cols() * opts.cell_size;
-    let height = board.rows() * opts.cell_size + 20;
+    let drawn = |row: usize, col: usize| board.grid[row][col];
+    write_svg(board, &caption, &opts, drawn, |w, rect, row, col| {
+        match opts.age_gradient {
+            Some(gradient) => {
+                let [r, g, b] = age_color(gradient, board.age(row, col));
+                let fill = format!("#{:02x}{:02x}{:02x}", r, g, b);
+                w.write_event(Event::Empty(refill(&rect, &fill)))?;
+            }
+            None => w.write_event(Event::Empty(rect))?,
+        }
+        Ok(())
+    })
+}
+
+/// The color of a cell of `age` on a `(young, old)` gradient.
+fn age_color((young, old): Gradient, age: u32) -> [u8; 3] {
+    let t = (age.clamp(1, AGE_RAMP) - 1) as f64 / (AGE_RAMP - 1) as f64;
+    std::array::from_fn(|i| (young[i] as f64 + (old[i] as f64 - young[i] as f64) * t).round() as u8)
+}
+
+/// A copy of a cell's rect with its fill replaced.
+fn refill(rect: &BytesStart<'static>, fill: &str) -> BytesStart<'static> {
+    let mut refilled = BytesStart::new("rect");
+    refilled.extend_attributes(
+        rect.attributes()
+            .flatten()
+            .map(|attr| match attr.key.as_ref() {
+                b"fill" => ("fill", fill).into(),
+                _ => attr,
+            }),
+    );
+    refilled
+}
+
+/// Colors each cell by the share of the next `steps` generations (starting
+/// with the current one) it is alive in, tracing where activity happened.
+pub fn activity_map(
+    game: &Game,
+    steps: usize,
+    opts: SVGOptions,
+) -> Result<String, quick_xml::Error> {
+    let mut game = game.clone();
+    let start = game.generation;
+    let mut counts = vec![vec![0usize; game.board.cols()]; game.board.rows()];
+
+    for step in 0..steps {
+        if step > 0 {
+            // expanding boards grow as they step, the counts grow with them
+            game.board.grow_like_next(&mut counts, 0);
+            game.next();
+        }
+        for (row, cells) in game.board.grid.iter().enumerate() {
+            for (col, cell) in cells.iter().enumerate() {
+                counts[row][col] += *cell as usize;
+            }
+        }
+    }
+
+    let caption = format!("t = {}..{}", start, game.generation);
+    let drawn = |row: usize, col: usize| counts[row][col] > 0;
+    write_svg(
+        &game.board,
+        &caption,
+        &opts,
+        drawn,
+        |w, mut rect, row, col| {
+            let opacity = counts[row][col] as f64 / steps as f64;
+            if opacity < 1.0 {
+                rect.push_attribute(("fill-opacity", &*opts.coord(opacity)));
+            }
+            w.write_event(Event::Empty(rect))?;
+            Ok(())
+        },
+    )
+}
+
+/// Animates the next `frames` generations (starting with the current one)
+/// with SMIL, looping every `frames * frame_duration` milliseconds. Each
+/// cell is drawn once and only toggles its visibility when it changes, so
+/// cells that stay alive cost no more than in a still render.
+pub fn svg_animated(
+    game: &Game,
+    frames: usize,
+    opts: SVGOptions,
+) -> Result<String, quick_xml::Error> {
+    let frames = frames.max(1);
+    let mut game = game.clone();
+    let start = game.generation;
+    let mut first = game.board.grid.clone();
+
+    // only the frames each cell toggles in are kept, not every board
+    let mut toggles = vec![vec![Vec::new(); game.board.cols()]; game.board.rows()];
+    for frame in 1..frames {
+        let mut prev = game.board.grid.clone();
+        // expanding boards grow as they step, everything kept grows with them
+        for grid in [&mut first, &mut prev] {
+            game.board.grow_like_next(grid, false);
+        }
+        game.board.grow_like_next(&mut toggles, Vec::new());
+        game.next();
+        for (row, (cells, prev_cells)) in game.board.grid.iter().zip(&prev).enumerate() {
+            for (col, (cell, prev_cell)) in cells.iter().zip(prev_cells).enumerate() {
+                if cell != prev_cell {
+                    toggles[row][col].push(frame);
+                }
+            }
+        }
+    }
+
+    let dur = format!("{}ms", frames * opts.frame_duration);
+    let caption = format!("t = {}..{}", start, game.generation);
+    // cells that are dead throughout are never drawn
+    let drawn = |row: usize, col: usize| first[row][col] || !toggles[row][col].is_empty();
+    write_svg(
+        &game.board,
+        &caption,
+        &opts,
+        drawn,
+        |w, mut rect, row, col| {
+            let alive = first[row][col];
+            let toggles = &toggles[row][col];
+            if toggles.is_empty() {
+                w.write_event(Event::Empty(rect))?;
+                return Ok(());
+            }
+
+            let visibility = |alive: bool| if alive { "visible" } else { "hidden" };
+            let (mut values, mut key_times) = (vec![visibility(alive)], vec!["0".to_string()]);
+            for (i, frame) in toggles.iter().enumerate() {
+                values.push(visibility(alive ^ (i % 2 == 0)));
+                // fixed precision, keyTimes must stay strictly increasing
+                let time = format!("{:.4}", *frame as f64 / frames as f64);
+                key_times.push(time.trim_end_matches('0').trim_end_matches('.').to_string());
+            }
+
+            rect.push_attribute(("visibility", visibility(alive)));
+            w.write_event(Event::Start(rect))?;
+            w.write_event(Event::Empty(BytesStart::new("animate").with_attributes(
+                vec![
+                    ("attributeName", "visibility"),
+                    ("values", &*values.join(";")),
+                    ("keyTimes", &*key_times.join(";")),
+                    ("calcMode", "discrete"),
+                    ("dur", &*dur),
+                    ("repeatCount", "indefinite"),
+                ],
+            )))?;
+            w.write_event(Event::End(BytesEnd::new("rect")))?;
+            Ok(())
+        },
+    )
+}
+
+/// Fills in the placeholders of a caption template, leaving anything else,
+/// unknown placeholders included, as written. It's escaped when written.
+fn caption(template: &str, game: &Game) -> String {
+    [
+        ("{gen}", game.generation.to_string()),
+        ("{delta}", game.delta.to_string()),
+        ("{rows}", game.board.rows().to_string()),
+        ("{cols}", game.board.cols().to_string()),
+        ("{pop}", game.board.population().to_string()),
+        ("{rule}", game.board.rule.to_string()),
+    ]
+    .iter()
+    .fold(template.to_string(), |caption, (placeholder, value)| {
+        caption.replace(placeholder, value)
+    })
+}
+
+/// Lays out a board-sized SVG with the title, ruler and caption, handing
+/// `cell` the rect of every cell `drawn` picks to write, altered or not.
+/// Rects are only built for drawn cells, most of a board is usually dead.
+fn write_svg(
+    board: &Board,
+    caption: &str,
+    opts: &SVGOptions,
+    drawn: impl Fn(usize, usize) -> bool,
+    mut cell: impl FnMut(
+        &mut SVGWriter,
+        BytesStart<'static>,
+        usize,
+        usize,
+    ) -> Result<(), quick_xml::Error>,
+) -> Result<String, quick_xml::Error> {
+    let cell_size = opts.cell_size as f64;
+    let band_height = opts.band_height();
+    let top = if opts.title.is_some() {
+        band_height
+    } else {
+        0.0
+    };
+    // the ruler needs a band on top and a margin on the left wide enough
+    // for the largest row label
+    let (left, top) = match opts.ruler {
+        Some(_) => {
+            let digits = board.rows().saturating_sub(1).max(1).ilog10() as f64 + 1.0;
+            let char_width = RULER_CHAR_WIDTH * opts.font_size as f64 / FONT_SIZE as f64;
+            (digits * char_width + 2.0 * RULER_TICK, top + band_height)
+        }
+        None => (0.0, top),
+    };
+    let caption_height = if opts.shows_caption() {
+        band_height
+    } else {
+        0.0
+    };
+    let width = left + board.cols() as f64 * cell_size;
+    let height = top + board.rows() as f64 * cell_size + caption_height;
+    let view_box = format!("0 0 {} {}", opts.coord(width), opts.coord(height));
 
     let mut w = Writer::new(std::io::Cursor::new(Vec::<u8>::new()));
 
     w.write_event(Event::Start(BytesStart::new("svg").with_attributes(vec![
         ("xmlns", "http://www.w3.org/2000/svg"),
-        ("width", &*format!("{}", width)),
-        ("height", &*format!("{}", height)),
+        ("width", &*opts.coord(width)),
+        ("height", &*opts.coord(height)),
+        ("viewBox", &*view_box),
     ])))?;
 
+    if let Some(title) = &opts.title {
+        write_text(&mut w, ("50%", band_height - 5.0), "middle", title, opts)?;
+    }
+
+    if let Some(step) = opts.ruler {
+        write_ruler(&mut w, board, (left, top), step, opts)?;
+    }
+
+    for row in 0..board.rows() {
+        for col in (0..board.cols()).filter(|&col| drawn(row, col)) {
+            let rect = BytesStart::new("rect").with_attributes(vec![
+                ("x", &*opts.coord(left + col as f64 * cell_size)),
+                ("y", &*opts.coord(top + row as f64 * cell_size)),
+                ("width", &*opts.coord(cell_size)),
+                ("height", &*opts.coord(cell_size)),
+                ("fill", &opts.fill_color),
+                ("stroke", &opts.stroke_color),
+                ("stroke-width", &*opts.coord(opts.stroke_width as f64)),
+            ]);
+            cell(&mut w, rect, row, col)?;
+        }
+    }
+
+    if opts.shows_caption() {
+        write_text(&mut w, ("50%", height - 5.0), "middle", caption, opts)?;
+    }
+
+    w.write_event(Event::End(BytesEnd::new("svg")))?;
+    Ok(std::str::from_utf8(&w.into_inner().into_inner())?.to_string())
+}
+
+/// Options for [`gif`], cells are styled like in SVG.
+#[derive(Clone)]
+pub struct GifOptions {
+    pub cell_size: usize,
+    pub stroke_width: usize,
+    pub stroke_color: String,
+    pub fill_color: String,
+    /// Milliseconds each generation shows for, rounded down to the GIF's
+    /// hundredths of a second.
+    pub delay: usize,
+}
+
+impl GifOptions {
+    pub fn new(svg: &SVGOptions, delay: Option<usize>) -> Self {
+        Self {
+            cell_size: svg.cell_size,
+            stroke_width: svg.stroke_width,
+            stroke_color: svg.stroke_color.clone(),
+            fill_color: svg.fill_color.clone(),
+            delay: delay.unwrap_or(100),
+        }
+    }
+}
+
+impl Default for GifOptions {
+    fn default() -> Self {
+        Self::new(&SVGOptions::default(), None)
+    }
+}
+
+/// Pixel width and height of a board drawn with `cell_size` cells, at least
+/// one pixel each.
+fn image_size(board: &Board, cell_size: usize) -> (usize, usize) {
+    (
+        board.cols().saturating_mul(cell_size).max(1),
+        board.rows().saturating_mul(cell_size).max(1),
+    )
+}
+
+/// The palette of a raster image: the transparent background, then the fill
+/// and stroke colors, then the ages of an age gradient if there is one.
+/// Errors with the color that can't be parsed.
+fn palette(
+    fill_color: &str,
+    stroke_color: &str,
+    age_gradient: Option<Gradient>,
+) -> Result<Vec<u8>, String> {
+    let mut palette = [
+        [0, 0, 0],
+        parse_color(fill_color)?,
+        parse_color(stroke_color)?,
+    ]
+    .concat();
+    if let Some(gradient) = age_gradient {
+        palette.extend((1..=AGE_RAMP).flat_map(|age| age_color(gradient, age)));
+    }
+    Ok(palette)
+}
+
+/// Parses a CSS color like `red` or `#ff0000` into its RGB channels, ignoring
+/// any alpha. Errors with the color that can't be parsed.
+pub fn parse_color(color: &str) -> Result<[u8; 3], String> {
+    match color.parse::<svgtypes::Color>() {
+        Ok(c) => Ok([c.red, c.green, c.blue]),
+        Err(_) => Err(color.to_string()),
+    }
+}
+
+/// Draws the alive cells of a board like the rects of [`svg`] into `pixels`,
+/// a `width` wide image of [`palette`] indices, filling each with the index
+/// `fill` gives for its row and column.
+fn rasterize(
+    board: &Board,
+    cell_size: usize,
+    stroke_width: usize,
+    width: usize,
+    pixels: &mut [u8],
+    fill: impl Fn(usize, usize) -> u8,
+) {
+    let stroke = 2;
+    let height = pixels.len() / width;
+    // like an SVG stroke, half of it is outside the cell and may cover the
+    // neighbors drawn before it
+    let outside = stroke_width / 2;
+    let inside = stroke_width - outside;
+
+    pixels.fill(0);
     for (row, cells) in board.grid.iter().enumerate() {
-        for (col, cell) in cells.iter().enumerate() {
-            if *cell {
-                w.write_event(Event::Empty(BytesStart::new("rect").with_attributes(vec![
-                    ("x", &*format!("{}", col * opts.cell_size)),
-                    ("y", &*format!("{}", row * opts.cell_size)),
-                    ("width", &*format!("{}", opts.cell_size)),
-                    ("height", &*format!("{}", opts.cell_size)),
-                    ("fill", &opts.fill_color),
-                    ("stroke", &opts.stroke_color),
-                    ("stroke-width", &*format!("{}", opts.stroke_width)),
-                ])))?;
+        for (col, _) in cells.iter().enumerate().filter(|(_, alive)| **alive) {
+            let (x, y) = (col * cell_size, row * cell_size);
+            for py in y.saturating_sub(outside)..(y + cell_size + outside).min(height) {
+                for px in x.saturating_sub(outside)..(x + cell_size + outside).min(width) {
+                    let filled = (x + inside..(x + cell_size).saturating_sub(inside)).contains(&px)
+                        && (y + inside..(y + cell_size).saturating_sub(inside)).contains(&py);
+                    pixels[py * width + px] = if filled { fill(row, col) } else { stroke };
+                }
             }
         }
     }
+}
 
-    w.write_event(Event::Start(BytesStart::new("text").with_attributes(vec![
-        ("x", "50%"),
-        ("y", &*format!("{}", height - 5)),
-        ("font-family", "monospace"),
-        ("font-size", "12"),
-        ("fill", &opts.fill_color),
-        ("dominant-baseline", "center"),
-        ("text-anchor", "middle"),
+/// Animates the next `frames` generations (starting with the current one)
+/// as a looping GIF, at most [`MAX_FRAMES`] and [`MAX_GIF_PIXELS`] in total.
+/// Alive cells are drawn like the rects of [`svg`] on a transparent
+/// background, without the caption.
+pub fn gif(game: &Game, opts: GifOptions, frames: usize) -> Result<Vec<u8>, GifError> {
+    let frames = frames.clamp(1, MAX_FRAMES);
+    let cell_size = opts.cell_size.max(1);
+    let (width, height) = image_size(&game.board, cell_size);
+    if width.saturating_mul(height) > MAX_IMAGE_PIXELS
+        || width > u16::MAX as usize
+        || height > u16::MAX as usize
+    {
+        return Err(GifError::TooLarge {
+            width,
+            height,
+            max: MAX_IMAGE_PIXELS,
+        });
+    }
+    if frames.saturating_mul(width * height) > MAX_GIF_PIXELS {
+        return Err(GifError::TooManyFrames {
+            frames,
+            width,
+            height,
+            max: MAX_GIF_PIXELS,
+        });
+    }
+
+    let palette =
+        palette(&opts.fill_color, &opts.stroke_color, None).map_err(GifError::InvalidColor)?;
+    let mut encoder = gif::Encoder::new(Vec::new(), width as u16, height as u16, &palette)?;
+    encoder.set_repeat(gif::Repeat::Infinite)?;
+
+    let delay = (opts.delay / 10).clamp(1, u16::MAX as usize) as u16;
+    let mut game = game.clone();
+    let mut pixels = vec![0; width * height];
+
+    for frame in 0..frames {
+        if frame > 0 {
+            game.next();
+        }
+
+        rasterize(
+            &game.board,
+            cell_size,
+            opts.stroke_width,
+            width,
+            &mut pixels,
+            |_, _| 1,
+        );
+        encoder.write_frame(&gif::Frame {
+            width: width as u16,
+            height: height as u16,
+            buffer: Cow::Borrowed(&pixels),
+            delay,
+            transparent: Some(0),
+            // clear each frame, the previous one would show through
+            dispose: gif::DisposalMethod::Background,
+            ..Default::default()
+        })?;
+    }
+
+    Ok(encoder.into_inner().map_err(gif::EncodingError::from)?)
+}
+
+/// Rasterizes the board as an indexed PNG, with alive cells drawn like the
+/// rects of [`svg`] on a transparent background. The title, ruler and
+/// caption are left out.
+pub fn png(game: &Game, opts: SVGOptions) -> Result<Vec<u8>, PngError> {
+    let cell_size = opts.cell_size.max(1);
+    let (width, height) = image_size(&game.board, cell_size);
+    if width.saturating_mul(height) > MAX_IMAGE_PIXELS {
+        return Err(PngError::TooLarge {
+            width,
+            height,
+            max: MAX_IMAGE_PIXELS,
+        });
+    }
+
+    let palette = palette(&opts.fill_color, &opts.stroke_color, opts.age_gradient)
+        .map_err(PngError::InvalidColor)?;
+    let mut pixels = vec![0; width * height];
+    // ages follow the fill and stroke in the palette
+    let board = &game.board;
+    rasterize(
+        board,
+        cell_size,
+        opts.stroke_width,
+        width,
+        &mut pixels,
+        |row, col| match opts.age_gradient {
+            Some(_) => 2 + board.age(row, col).min(AGE_RAMP) as u8,
+            None => 1,
+        },
+    );
+
+    let mut result = Vec::new();
+    let mut encoder = png::Encoder::new(&mut result, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Indexed);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.set_palette(palette);
+    // only the background is transparent
+    encoder.set_trns(vec![0]);
+    let mut writer = encoder.write_header()?;
+    writer.write_image_data(&pixels)?;
+    writer.finish()?;
+
+    Ok(result)
+}
+
+pub fn render(
+    game: &Game,
+    format: RenderFormat,
+    opts: RenderOptions,
+) -> Result<Vec<u8>, RenderError> {
+    Ok(match format {
+        RenderFormat::Text => text(game, opts.text).into_bytes(),
+        RenderFormat::Svg => match opts.activity {
+            Some(steps) => activity_map(game, steps, opts.svg)?.into_bytes(),
+            None => svg(game, opts.svg)?.into_bytes(),
+        },
+        RenderFormat::AnimatedSvg => {
+            svg_animated(game, opts.frames.unwrap_or(20), opts.svg)?.into_bytes()
+        }
+        RenderFormat::Html => html(game, &opts.name, opts.svg)?.into_bytes(),
+        RenderFormat::Npy => npy(game),
+        RenderFormat::Rle => rle(game).into_bytes(),
+        RenderFormat::Gif => gif(
+            game,
+            GifOptions::new(&opts.svg, opts.delay),
+            opts.frames.unwrap_or(20),
+        )?,
+        RenderFormat::Png => png(game, opts.svg)?,
+        RenderFormat::Json => serde_json::to_vec(&GameState(game))?,
+        RenderFormat::Life106 => life106(game).into_bytes(),
+        RenderFormat::Ansi => ansi(game, opts.ansi).into_bytes(),
+        RenderFormat::Cells => cells(game, &opts.name).into_bytes(),
+        RenderFormat::Diff => diff(game, opts.text).into_bytes(),
+    })
+}
+
+/// Hex xxh64 (seed 0) of a rendered body, so clients can verify they
+/// received all of it.
+pub fn checksum(body: &[u8]) -> String {
+    format!("{:016x}", xxh64(body, 0))
+}
+
+/// The `x-life-generation` and `x-life-delta` headers for a game.
+pub fn metadata(game: &Game) -> HeaderMap {
+    let mut headers = HeaderMap::new();
+    headers.insert("x-life-generation", HeaderValue::from(game.generation));
+    headers.insert("x-life-delta", HeaderValue::from(game.delta));
+    headers
+}
+
+impl Game {
+    /// Advances the game once and renders it, like
+    /// [`Game::render_with_headers`].
+    pub fn step_and_render(
+        &mut self,
+        format: RenderFormat,
+        opts: RenderOptions,
+    ) -> Result<(Vec<u8>, HeaderMap), RenderError> {
+        self.next();
+        self.render_with_headers(format, opts)
+    }
+
+    /// Renders the game, returning the body along with the headers the
+    /// server sends: content type, checksum and the game's metadata.
+    pub fn render_with_headers(
+        &self,
+        format: RenderFormat,
+        opts: RenderOptions,
+    ) -> Result<(Vec<u8>, HeaderMap), RenderError> {
+        let body = render(self, format, opts)?;
+
+        let mut headers = metadata(self);
+        headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static(format.content_type()),
+        );
+        let checksum = HeaderValue::from_str(&checksum(&body)).expect("hex is a valid header");
+        headers.insert("x-life-checksum", checksum);
+
+        Ok((body, headers))
+    }
+}
+
+/// Tiles the SVG renders of several games into a grid `cols` wide, each
+/// labelled with its name. Tiles are spaced by the largest one.
+pub fn montage(
+    games: &[(&str, &Game)],
+    cols: usize,
+    opts: SVGOptions,
+) -> Result<String, quick_xml::Error> {
+    let cols = cols.clamp(1, games.len().max(1));
+    let rows = games.len().div_ceil(cols);
+    let cell_size = opts.cell_size as f64;
+
+    let tile_width = games
+        .iter()
+        .map(|(_, g)| g.board.cols() as f64 * cell_size)
+        .fold(0.0, f64::max);
+    let tile_height = games
+        .iter()
+        .map(|(_, g)| g.board.rows() as f64 * cell_size + opts.band_height())
+        .map(|height| {
+            height
+                + if opts.shows_caption() {
+                    opts.band_height()
+                } else {
+                    0.0
+                }
+        })
+        .fold(0.0, f64::max);
+    let width = cols as f64 * (tile_width + MONTAGE_GAP) - MONTAGE_GAP;
+    let height = rows as f64 * (tile_height + MONTAGE_GAP) - MONTAGE_GAP;
+
+    let mut w = Writer::new(std::io::Cursor::new(Vec::<u8>::new()));
+
+    w.write_event(Event::Start(BytesStart::new("svg").with_attributes(vec![
+        ("xmlns", "http://www.w3.org/2000/svg"),
+        ("width", &*opts.coord(width.max(0.0))),
+        ("height", &*opts.coord(height.max(0.0))),
     ])))?;
-    w.write_event(Event::Text(BytesText::new(&*format!(
-        "t = {}, Δ = {}",
-        game.generation, game.delta
-    ))))?;
-    w.write_event(Event::End(BytesEnd::new("text")))?;
+
+    for (i, (name, game)) in games.iter().enumerate() {
+        let x = (i % cols) as f64 * (tile_width + MONTAGE_GAP);
+        let y = (i / cols) as f64 * (tile_height + MONTAGE_GAP);
+        let tile = svg(
+            game,
+            SVGOptions {
+                title: Some(name.to_string()),
+                ..opts.clone()
+            },
+        )?;
+
+        let translate = format!("translate({} {})", opts.coord(x), opts.coord(y));
+        w.write_event(Event::Start(
+            BytesStart::new("g").with_attributes(vec![("transform", &*translate)]),
+        ))?;
+        w.get_mut().write_all(tile.as_bytes())?;
+        w.write_event(Event::End(BytesEnd::new("g")))?;
+    }
 
     w.write_event(Event::End(BytesEnd::new("svg")))?;
     Ok(std::str::from_utf8(&w.into_inner().into_inner())?.to_string())
 }
+
+/// Wraps the SVG rendering in a standalone page with buttons that advance the
+/// game in place by fetching `/:name.svg?next=true` with the page's options.
+pub fn html(game: &Game, name: &str, opts: SVGOptions) -> Result<String, quick_xml::Error> {
+    let svg = svg(game, opts)?;
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+</head>
+<body>
+<div id="board">{svg}</div>
+<button id="next">next</button>
+<button id="play">play</button>
+<script>
+const board = document.getElementById("board");
+const next = async () => {{
+  const params = new URLSearchParams(location.search);
+  params.set("next", "true");
+  const res = await fetch(location.pathname.replace(/\.html$/, ".svg") + "?" + params);
+  board.innerHTML = await res.text();
+}};
+let timer;
+document.getElementById("next").onclick = next;
+document.getElementById("play").onclick = (e) => {{
+  if (timer) {{
+    clearInterval(timer);
+    timer = undefined;
+    e.target.textContent = "play";
+  }} else {{
+    timer = setInterval(next, 500);
+    e.target.textContent = "pause";
+  }}
+}};
+</script>
+</body>
+</html>
+"#,
+        name = escape(name),
+        svg = svg,
+    ))
+}
+
+/// A page that shows the SVG rendering and keeps advancing it, fetching
+/// `svg_url` (which should step the game) every `interval` milliseconds. The
+/// URL and interval are passed to the script as data attributes, so they're
+/// only ever escaped as HTML.
+pub fn viewer(
+    game: &Game,
+    name: &str,
+    svg_url: &str,
+    interval: u64,
+    opts: SVGOptions,
+) -> Result<String, quick_xml::Error> {
+    let svg = svg(game, opts)?;
+
+    Ok(format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{name}</title>
+</head>
+<body>
+<div id="board" data-src="{src}" data-interval="{interval}">{svg}</div>
+<script>
+const board = document.getElementById("board");
+setInterval(async () => {{
+  const res = await fetch(board.dataset.src);
+  if (res.ok) {{
+    board.innerHTML = await res.text();
+  }}
+}}, Number(board.dataset.interval));
+</script>
+</body>
+</html>
+"#,
+        name = escape(name),
+        src = escape(svg_url),
+        interval = interval,
+        svg = svg,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Boundary;
+
+    fn game(seed: &str) -> Game {
+        Game::from(Board::from_seed(seed.to_string(), Default::default()).unwrap())
+    }
+
+    fn glider(boundary: Boundary) -> Game {
+        let mut game = game(".#.\n..#\n###");
+        game.board.boundary = boundary;
+        game
+    }
+
+    #[test]
+    fn animates_expanding_boards() {
+        let svg = svg_animated(&glider(Boundary::Expanding), 10, SVGOptions::default()).unwrap();
+        let mut stepped = glider(Boundary::Expanding);
+        (0..9).for_each(|_| stepped.next());
+        let width = stepped.board.cols() * 20;
+        assert!(stepped.board.cols() > 3);
+        assert!(svg.starts_with(&format!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}""#,
+            width
+        )));
+    }
+
+    #[test]
+    fn maps_activity_of_expanding_boards() {
+        let svg = activity_map(&glider(Boundary::Expanding), 10, SVGOptions::default()).unwrap();
+        assert!(svg.contains("fill-opacity"));
+    }
+
+    #[test]
+    fn gif_limits_total_pixels() {
+        let game = Game::from(Board::new(vec![vec![false; 200]; 200]));
+        let opts = GifOptions {
+            cell_size: 10,
+            ..Default::default()
+        };
+        assert!(gif(&game, opts.clone(), 1).is_ok());
+        assert!(matches!(
+            gif(&game, opts, 17),
+            Err(GifError::TooManyFrames { frames: 17, .. })
+        ));
+    }
+
+    #[test]
+    fn clamps_zero_and_oversized_options() {
+        let zero = SVGOptions::new(Some(0), Some(5), None, None, None);
+        assert_eq!((zero.cell_size, zero.stroke_width), (1, 1));
+        let huge = SVGOptions::new(Some(1_000_000), None, None, None, Some(100));
+        assert_eq!(huge.cell_size, MAX_CELL_SIZE);
+        assert_eq!(huge.precision, MAX_PRECISION);
+
+        let svg = svg(&game("##\n##"), zero).unwrap();
+        assert!(svg.contains(r#"width="2""#), "{svg}");
+    }
+
+    #[test]
+    fn writes_rle_like_golly() {
+        assert_eq!(
+            rle(&game(".#.\n..#\n###")),
+            "x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!\n"
+        );
+        let mut highlife = game("#.\n..\n.#");
+        highlife.board.rule = Rule::parse("B36/S23").unwrap();
+        assert_eq!(rle(&highlife), "x = 2, y = 3, rule = B36/S23\no2$bo!\n");
+    }
+
+    #[test]
+    fn rle_round_trips() {
+        let mut highlife = game(".#.\n..#\n###\n...\n...");
+        highlife.board.rule = Rule::parse("B36/S23").unwrap();
+        for game in [
+            Game::from(Board::new(vec![vec![false; 4]; 3])),
+            game("#"),
+            game(".#.\n..#\n###\n...\n..."),
+            highlife,
+        ] {
+            let board = Board::from_rle(&rle(&game)).unwrap();
+            assert_eq!(board.grid, game.board.grid);
+            assert_eq!(board.rule, game.board.rule);
+        }
+    }
+
+    #[test]
+    fn gif_has_a_frame_per_generation() {
+        let opts = GifOptions {
+            cell_size: 4,
+            ..Default::default()
+        };
+        let bytes = gif(&glider(Boundary::Dead), opts, 5).unwrap();
+        let mut decoder = gif::DecodeOptions::new().read_info(&bytes[..]).unwrap();
+        assert_eq!((decoder.width(), decoder.height()), (12, 12));
+        let mut frames = 0;
+        while decoder.read_next_frame().unwrap().is_some() {
+            frames += 1;
+        }
+        assert_eq!(frames, 5);
+    }
+
+    #[test]
+    fn rejects_zero_and_oversized_options() {
+        assert_eq!(
+            SVGOptions::validate(Some(0), None, None, None),
+            Err(OptionsError::CellSize {
+                value: 0,
+                max: MAX_CELL_SIZE
+            })
+        );
+        assert!(SVGOptions::validate(Some(MAX_CELL_SIZE + 1), None, None, None).is_err());
+        assert!(SVGOptions::validate(Some(1_000_000), None, None, None).is_err());
+        assert_eq!(
+            SVGOptions::validate(Some(10), Some(11), None, None),
+            Err(OptionsError::StrokeWidth {
+                value: 11,
+                cell_size: 10
+            })
+        );
+        assert!(SVGOptions::validate(None, None, Some(MAX_PRECISION + 1), None).is_err());
+        assert!(SVGOptions::validate(None, None, None, Some(0)).is_err());
+        assert!(SVGOptions::validate(Some(MAX_CELL_SIZE), Some(MAX_CELL_SIZE), None, None).is_ok());
+    }
+
+    #[test]
+    fn fills_caption_templates() {
+        let opts = SVGOptions {
+            caption_template: Some("gen {gen} of {rows}x{cols}, {pop} alive, {rule}".to_string()),
+            ..Default::default()
+        };
+        let svg = svg(&game(".#.\n..#\n###"), opts).unwrap();
+        assert!(svg.contains("gen 0 of 3x3, 5 alive, B3/S23"), "{svg}");
+    }
+
+    #[test]
+    fn escapes_caption_templates() {
+        let opts = SVGOptions {
+            caption_template: Some("<b> & {pop}".to_string()),
+            font_family: "serif".to_string(),
+            font_size: 16,
+            ..Default::default()
+        };
+        let svg = svg(&game("#"), opts).unwrap();
+        assert!(svg.contains("&lt;b&gt; &amp; 1"), "{svg}");
+        assert!(!svg.contains("<b>"));
+        assert!(svg.contains(r#"font-family="serif""#), "{svg}");
+        assert!(svg.contains(r#"font-size="16""#), "{svg}");
+    }
+
+    #[test]
+    fn empty_caption_template_hides_the_band() {
+        let hidden = SVGOptions {
+            caption_template: Some(String::new()),
+            ..Default::default()
+        };
+        let svg = svg(&game("#"), hidden).unwrap();
+        assert!(svg.contains(r#"height="20""#), "{svg}");
+        assert!(!svg.contains("<text"));
+    }
+
+    #[test]
+    fn diffs_a_blinker() {
+        let blinker = game(".....\n..#..\n..#..\n..#..\n.....");
+        assert_eq!(
+            diff(&blinker, TextOptions::new(None, None, None)),
+            ".....\n..-..\n.+#+.\n..-..\n....."
+        );
+    }
+
+    #[test]
+    fn negotiates_formats() {
+        let negotiate = |accept, ext| RenderFormat::negotiate(accept, ext);
+        assert_eq!(negotiate(Some("image/svg+xml"), None), RenderFormat::Svg);
+        assert_eq!(
+            negotiate(Some("application/json"), None),
+            RenderFormat::Json
+        );
+        assert_eq!(negotiate(None, Some("svg")), RenderFormat::Svg);
+        // an extension wins over Accept
+        assert_eq!(
+            negotiate(Some("image/svg+xml"), Some("txt")),
+            RenderFormat::Text
+        );
+    }
+
+    #[test]
+    fn negotiates_by_quality() {
+        let negotiate = |accept| RenderFormat::negotiate(Some(accept), None);
+        assert_eq!(
+            negotiate("text/plain;q=0.5, image/svg+xml;q=0.9"),
+            RenderFormat::Svg
+        );
+        assert_eq!(
+            negotiate("image/svg+xml;q=0, application/json"),
+            RenderFormat::Json
+        );
+        // equally preferred types keep the client's order
+        assert_eq!(
+            negotiate("application/json, image/svg+xml"),
+            RenderFormat::Json
+        );
+        assert_eq!(
+            negotiate("image/svg+xml;q=oops, text/plain;q=0.1"),
+            RenderFormat::Text
+        );
+    }
+
+    #[test]
+    fn negotiates_text_without_a_match() {
+        for accept in ["*/*", "image/*", "text/html, image/png", ""] {
+            assert_eq!(
+                RenderFormat::negotiate(Some(accept), None),
+                RenderFormat::Text,
+                "{accept}"
+            );
+        }
+        assert_eq!(RenderFormat::negotiate(None, None), RenderFormat::Text);
+    }
+
+    #[test]
+    fn draws_only_live_cells() {
+        let game = game("....\n.##.\n....");
+        let svg = svg(&game, SVGOptions::default()).unwrap();
+        assert_eq!(svg.matches("<rect").count(), 2);
+
+        let svg = svg_animated(&game, 3, SVGOptions::default()).unwrap();
+        assert_eq!(svg.matches("<rect").count(), 2);
+    }
+
+    #[test]
+    fn steps_and_renders_with_headers() {
+        let mut game = game(".#.\n.#.\n.#.");
+        let (body, headers) = game
+            .step_and_render(RenderFormat::Text, Default::default())
+            .unwrap();
+        assert_eq!(body, b"...\n###\n...");
+        assert_eq!(headers["x-life-generation"], "1");
+        assert_eq!(headers["x-life-delta"], "4");
+        assert_eq!(
+            headers[header::CONTENT_TYPE],
+            RenderFormat::Text.content_type()
+        );
+        assert_eq!(headers["x-life-checksum"], checksum(&body).as_str());
+    }
+
+    #[test]
+    fn png_has_a_pixel_per_cell_at_cell_size_one() {
+        let mut grid = vec![vec![false; 1500]; 1000];
+        grid[500][700] = true;
+        let opts = SVGOptions {
+            cell_size: 1,
+            ..Default::default()
+        };
+        let png = png(&Game::from(Board::new(grid)), opts.clone()).unwrap();
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(u32::from_be_bytes(png[16..20].try_into().unwrap()), 1500);
+        assert_eq!(u32::from_be_bytes(png[20..24].try_into().unwrap()), 1000);
+
+        let too_large = Game::from(Board::new(vec![vec![false; 2049]; 2048]));
+        assert!(matches!(
+            super::png(&too_large, opts),
+            Err(PngError::TooLarge { .. })
+        ));
+    }
+
+    #[test]
+    fn ansi_colors_each_row() {
+        let game = game("#.\n.#");
+        let opts = AnsiOptions::new(Some([255, 0, 0]), Some([0, 0, 255]), None);
+        assert_eq!(
+            ansi(&game, opts),
+            "\x1b[38;2;255;0;0;48;2;0;0;255m██  \x1b[0m\n\x1b[38;2;255;0;0;48;2;0;0;255m  ██\x1b[0m\n"
+        );
+        assert_eq!(
+            ansi(&game, AnsiOptions::new(None, None, Some(true))),
+            "██  \n  ██\n"
+        );
+    }
+
+    #[test]
+    fn svg_view_box_fits_the_caption() {
+        let glider = glider(Boundary::Dead);
+        let svg = svg(&glider, SVGOptions::default()).unwrap();
+        assert!(svg.contains(r#"width="60" height="80" viewBox="0 0 60 80""#));
+        assert!(svg.contains("<text"));
+
+        let opts = SVGOptions {
+            caption: false,
+            ..Default::default()
+        };
+        let svg = super::svg(&glider, opts).unwrap();
+        assert!(svg.contains(r#"width="60" height="60" viewBox="0 0 60 60""#));
+        assert!(!svg.contains("<text"));
+    }
+
+    #[test]
+    fn captions_name_non_conway_rules() {
+        let mut highlife = glider(Boundary::Dead);
+        let svg = svg(&highlife, SVGOptions::default()).unwrap();
+        assert!(svg.contains(">t = 0, Δ = 0</text>"), "{svg}");
+
+        highlife.board.rule = Rule::parse("B36/S23").unwrap();
+        let svg = super::svg(&highlife, SVGOptions::default()).unwrap();
+        assert!(svg.contains(">t = 0, Δ = 0, B36/S23</text>"), "{svg}");
+        let json = serde_json::to_value(GameState(&highlife)).unwrap();
+        assert_eq!(json["rule"], "B36/S23");
+    }
+
+    #[test]
+    fn game_state_json_round_trips() {
+        #[derive(Deserialize)]
+        struct State {
+            generation: usize,
+            delta: usize,
+            rows: usize,
+            cols: usize,
+            grid: Vec<Vec<bool>>,
+            terminal: bool,
+        }
+
+        let mut game = glider(Boundary::Dead);
+        game.next();
+        let json = serde_json::to_string(&GameState(&game)).unwrap();
+        let state: State = serde_json::from_str(&json).unwrap();
+        assert_eq!((state.generation, state.delta), (1, game.delta));
+        assert_eq!((state.rows, state.cols), (3, 3));
+        assert_eq!(state.grid, game.board.grid);
+        assert!(!state.terminal);
+    }
+
+    #[test]
+    fn npy_has_a_numpy_header() {
+        let npy = npy(&game("#..\n.##"));
+        assert_eq!(&npy[..8], b"\x93NUMPY\x01\x00");
+        let header_len = u16::from_le_bytes([npy[8], npy[9]]) as usize;
+        assert_eq!((10 + header_len) % 64, 0);
+
+        let header = std::str::from_utf8(&npy[10..10 + header_len]).unwrap();
+        assert!(header.starts_with("{'descr': '|u1', 'fortran_order': False, 'shape': (2, 3), }"));
+        assert!(header.ends_with(" \n"));
+        assert_eq!(&npy[10 + header_len..], [1, 0, 0, 0, 1, 1]);
+    }
+}